@@ -2,9 +2,156 @@
 
 //! Application configuration handling.
 
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
 use directories::BaseDirs;
 use serde::{Deserialize, Serialize};
 
+/// Environment variable naming the profile when `--profile` isn't given.
+pub const PROFILE_ENV: &str = "MULTI_RSS_PROFILE";
+/// Environment variable overriding the data directory (`--data-dir`).
+pub const DATA_DIR_ENV: &str = "MULTI_RSS_DATA_DIR";
+/// Environment variable overriding the config directory (`--config-dir`).
+pub const CONFIG_DIR_ENV: &str = "MULTI_RSS_CONFIG_DIR";
+
+/// Folder created under the platform config and data directories.
+pub const APP_DIR: &str = "multi-rss";
+/// Name [`APP_DIR`] had in earlier versions, moved on first start.
+const LEGACY_APP_DIR: &str = "rssq";
+
+/// Where configuration and data are kept. Chosen once at startup.
+#[derive(Debug, Default)]
+pub struct Dirs {
+    /// Namespaces both directories as `<dir>/<profile>`.
+    pub profile: Option<String>,
+    /// Used instead of the platform config directory's app folder ([`APP_DIR`]).
+    pub config: Option<PathBuf>,
+    /// Used instead of the platform data directory's app folder ([`APP_DIR`]).
+    pub data: Option<PathBuf>,
+    /// Never write config, data or session files, for directories that
+    /// can't be written.
+    pub read_only: bool,
+}
+
+impl Dirs {
+    /// Directory that would hold `config.toml` with these settings.
+    pub fn config_dir(&self) -> Option<PathBuf> {
+        app_dir(
+            self.config.as_deref(),
+            BaseDirs::new().map(|b| b.config_dir().to_path_buf()),
+            self.profile.as_deref(),
+        )
+    }
+
+    /// Directory that would hold the database with these settings.
+    pub fn data_dir(&self) -> Option<PathBuf> {
+        app_dir(
+            self.data.as_deref(),
+            BaseDirs::new().map(|b| b.data_dir().to_path_buf()),
+            self.profile.as_deref(),
+        )
+    }
+}
+
+static DIRS: OnceLock<Dirs> = OnceLock::new();
+
+/// Select the directories used for config and data, creating overridden
+/// ones unless read-only. Must be called before anything is loaded; later calls are ignored.
+/// Profile names may not contain path separators.
+pub fn set_dirs(mut dirs: Dirs) -> Result<(), String> {
+    dirs.profile = dirs.profile.filter(|n| !n.is_empty());
+    if let Some(n) = &dirs.profile
+        && (n.contains(['/', '\\']) || n == "." || n == "..")
+    {
+        return Err(format!("invalid profile name '{}'", n));
+    }
+    let create = [&dirs.config, &dirs.data].into_iter().flatten();
+    for dir in create.filter(|_| !dirs.read_only) {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+    }
+    let _ = DIRS.set(dirs);
+    Ok(())
+}
+
+/// Directory holding `config.toml`.
+pub fn config_dir() -> Option<PathBuf> {
+    match DIRS.get() {
+        Some(dirs) => dirs.config_dir(),
+        None => Dirs::default().config_dir(),
+    }
+}
+
+/// Directory holding the database and session files.
+pub fn data_dir() -> Option<PathBuf> {
+    match DIRS.get() {
+        Some(dirs) => dirs.data_dir(),
+        None => Dirs::default().data_dir(),
+    }
+}
+
+/// Move the folders of earlier versions, named [`LEGACY_APP_DIR`], to
+/// [`APP_DIR`] under the platform config and data directories. Folders are
+/// left alone when the new one already exists. Returns the moved folders'
+/// new paths.
+pub fn migrate_legacy_dirs() -> std::io::Result<Vec<PathBuf>> {
+    let Some(base) = BaseDirs::new() else {
+        return Ok(Vec::new());
+    };
+    let mut moved = Vec::new();
+    for parent in [base.config_dir(), base.data_dir()] {
+        if let Some(dir) = migrate_legacy_dir(parent)? {
+            moved.push(dir);
+        }
+    }
+    Ok(moved)
+}
+
+/// Rename `parent/LEGACY_APP_DIR` to `parent/APP_DIR` if only the former
+/// exists.
+fn migrate_legacy_dir(parent: &Path) -> std::io::Result<Option<PathBuf>> {
+    let legacy = parent.join(LEGACY_APP_DIR);
+    let current = parent.join(APP_DIR);
+    if !legacy.is_dir() || current.exists() {
+        return Ok(None);
+    }
+    std::fs::rename(&legacy, &current)?;
+    Ok(Some(current))
+}
+
+/// Whether saving is switched off because the directories aren't writable.
+pub fn read_only() -> bool {
+    DIRS.get().is_some_and(|d| d.read_only)
+}
+
+/// Check that files can be created in `dir`, creating it if needed.
+pub fn check_writable(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let probe = dir.join(".write-test");
+    std::fs::write(&probe, b"")?;
+    std::fs::remove_file(probe)
+}
+
+/// `overridden`, or [`APP_DIR`] under the platform directory `base`, followed by
+/// the profile name when one is selected.
+fn app_dir(
+    overridden: Option<&Path>,
+    base: Option<PathBuf>,
+    profile: Option<&str>,
+) -> Option<PathBuf> {
+    let dir = match overridden {
+        Some(dir) => dir.to_path_buf(),
+        None => base?.join(APP_DIR),
+    };
+    Some(match profile {
+        Some(profile) => dir.join(profile),
+        None => dir,
+    })
+}
+
 /// Global application configuration.
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Config {
@@ -13,20 +160,260 @@ pub struct Config {
     pub keys: Keys,
     #[serde(default)]
     pub refresh: Refresh,
+    #[serde(default)]
+    pub sync: StateSync,
+    #[serde(default)]
+    pub net: Net,
+    #[serde(default)]
+    pub schedule: Schedule,
+    #[serde(default)]
+    pub storage: Storage,
+}
+
+/// How the database is kept on disk.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Storage {
+    /// Store the database gzip-compressed as `db.json.gz` instead of
+    /// `db.json`. Either file is read regardless, and the other one is
+    /// removed on the next save.
+    #[serde(default)]
+    pub compress_db: bool,
+}
+
+/// Actions run at a set local time each day while the app is open.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Schedule {
+    /// Open the queue at this time (`HH:MM`), as if confirmed from the
+    /// queue. Skipped when the queue is empty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue_open_at: Option<String>,
+}
+
+impl Schedule {
+    /// `queue_open_at` as a time of day, or `None` when unset or invalid.
+    pub fn queue_open_time(&self) -> Option<chrono::NaiveTime> {
+        chrono::NaiveTime::parse_from_str(self.queue_open_at.as_deref()?.trim(), "%H:%M").ok()
+    }
+}
+
+/// Optional read-state sharing between machines.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StateSync {
+    /// File, e.g. in a synced folder, whose read/starred/queued flags are
+    /// merged at startup and on quit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_state_file: Option<PathBuf>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Ui {
     pub theme: Theme,
-    #[serde(default)]
+    /// Which items are listed by read state; cycled with `u`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_filter: Option<ReadFilter>,
+    /// Superseded by `read_filter`; only read so older configs keep working.
+    #[serde(default, skip_serializing)]
     pub unread_only: bool,
     #[serde(default)]
     pub sort: SortOrder,
+    /// List items oldest first (undated ones still last), Z to A, or in
+    /// reverse feed order, whichever sort applies; toggled with `S`.
+    #[serde(default)]
+    pub sort_reverse: bool,
+    /// Status bar template. Supported tokens: `{last_refresh}`, `{new_items}`,
+    /// `{total_unread}`, `{feed_url}`, `{selected_index}`, `{total}`,
+    /// `{refreshing}` and `{last_error}`.
+    #[serde(default = "default_status_format")]
+    pub status_format: String,
+    /// Wrap long item titles onto a second line in the item list.
+    #[serde(default)]
+    pub wrap_titles: bool,
+    /// Reopen on the group, feed and pane that were selected at last quit.
+    #[serde(default = "default_true")]
+    pub restore_session: bool,
+    /// Item row template. Supported tokens: `{badge}`, `{date}`, `{title}` and
+    /// `{feed}`. Must contain `{title}`; invalid templates fall back to the
+    /// default.
+    #[serde(default = "default_item_format")]
+    pub item_format: String,
+    /// One row per item using `item_format`, or the title with the date and
+    /// feed on a dimmed second line.
+    #[serde(default)]
+    pub item_list_density: ItemDensity,
+    /// `{badge}` of unread items.
+    #[serde(default = "default_unread_glyph")]
+    pub unread_glyph: String,
+    /// `{badge}` of read items.
+    #[serde(default = "default_read_glyph")]
+    pub read_glyph: String,
+    /// Emphasize items from today and dim those older than a week.
+    #[serde(default)]
+    pub color_by_age: bool,
+    /// Show feeds inline under expandable groups in the Groups pane.
+    #[serde(default)]
+    pub tree_view: bool,
+    /// Up on the first row of a list moves to the last one and Down on the
+    /// last row to the first.
+    #[serde(default)]
+    pub wrap_navigation: bool,
+    /// Show the groups with the most unread items at the right of the status
+    /// bar.
+    #[serde(default = "default_true")]
+    pub unread_summary: bool,
+    /// How many groups the unread summary lists.
+    #[serde(default = "default_unread_summary_groups")]
+    pub unread_summary_groups: usize,
+    /// Ask before deleting a group or feed.
+    #[serde(default = "default_true")]
+    pub confirm_delete: bool,
+    /// Ask before opening several items at once. Opening more than 20 items
+    /// always asks.
+    #[serde(default = "default_true")]
+    pub confirm_bulk_open: bool,
+    /// Ask before quitting while items are queued.
+    #[serde(default = "default_true")]
+    pub confirm_quit_with_queue: bool,
+    /// Group for new feeds added without choosing one: with `+`, with
+    /// `mrss add` without `--group`, or with `a` before any group exists.
+    /// Created the first time a feed is added to it.
+    #[serde(default)]
+    pub default_group: Option<String>,
+    /// Show the Preview pane; toggled with `P`.
+    #[serde(default = "default_true")]
+    pub show_preview: bool,
+    /// Cut item descriptions in the preview after this many characters;
+    /// `F` shows the rest. Unlimited when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_max_chars: Option<usize>,
+    /// Preview an item's full content instead of its summary when the feed
+    /// includes both. Feeds can override this with `C`.
+    #[serde(default)]
+    pub prefer_full_content: bool,
+    /// Hide items read more than this many days ago (by publication date for
+    /// items read before read times were recorded). They stay in the
+    /// database. Never hidden when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hide_read_after_days: Option<u32>,
+    /// Fold items whose titles are at least this similar (Jaro-Winkler,
+    /// 0.0 to 1.0) into the newest one, e.g. re-posted corrections. Off when
+    /// unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collapse_similar_titles: Option<f64>,
+    /// List only this many items per feed at first; `L` shows the next
+    /// page. Everything is listed when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<usize>,
+    /// Pane sizes and preview placement.
+    #[serde(default)]
+    pub layout: PaneLayout,
+    /// Mark items read when opening the queue.
+    #[serde(default = "default_true")]
+    pub queue_open_marks_read: bool,
+    /// Empty the queue after opening it; when off, items stay queued.
+    #[serde(default = "default_true")]
+    pub queue_open_clears: bool,
+    /// How `y` copies an item; cycled with `Y`.
+    #[serde(default)]
+    pub citation_style: CitationStyle,
+    /// Template for the `custom` citation style. Supported tokens:
+    /// `{title}`, `{author}`, `{date}`, `{link}` and `{feed}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub citation_template: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+/// Pane proportions in percent. `groups_pct`, `feeds_pct` and `items_pct`
+/// split the screen width and must add up to 100; `preview_pct` is the share
+/// of the items column given to the preview.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaneLayout {
+    pub groups_pct: u16,
+    pub feeds_pct: u16,
+    pub items_pct: u16,
+    pub preview_pct: u16,
+    pub preview_position: PreviewPosition,
+}
+
+impl Default for PaneLayout {
+    fn default() -> Self {
+        Self {
+            groups_pct: 20,
+            feeds_pct: 30,
+            items_pct: 50,
+            preview_pct: 50,
+            preview_position: PreviewPosition::Bottom,
+        }
+    }
+}
+
+impl PaneLayout {
+    /// The configured layout, or the default proportions if the percentages
+    /// are zero, don't add up to 100 or leave no room for the item list. The
+    /// preview position is kept either way.
+    pub fn validated(&self) -> Self {
+        let widths = [self.groups_pct, self.feeds_pct, self.items_pct];
+        if widths.contains(&0)
+            || widths.iter().sum::<u16>() != 100
+            || !(1..100).contains(&self.preview_pct)
+        {
+            Self {
+                preview_position: self.preview_position,
+                ..Self::default()
+            }
+        } else {
+            self.clone()
+        }
+    }
+}
+
+/// Where the preview is drawn relative to the item list.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewPosition {
+    Right,
+    #[default]
+    Bottom,
+    Hidden,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Opener {
     pub command: String,
+    /// Open single items with `command` or in the built-in reader. Bulk
+    /// opens always use `command`.
+    #[serde(default)]
+    pub mode: OpenerMode,
+    /// Keep focus in the terminal when opening links with the system
+    /// browser (empty `command`). Feeds can override this with `B`.
+    #[serde(default = "default_true")]
+    pub background: bool,
+}
+
+impl Default for Opener {
+    fn default() -> Self {
+        Self {
+            command: String::new(),
+            mode: OpenerMode::default(),
+            background: true,
+        }
+    }
+}
+
+/// How much room each entry takes in the item list.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemDensity {
+    #[default]
+    Compact,
+    Comfortable,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OpenerMode {
+    #[default]
+    External,
+    Internal,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -36,45 +423,279 @@ pub struct Keys {
     pub refresh: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Theme {
     Dark,
     Light,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Item filter by read state.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadFilter {
+    #[default]
+    All,
+    Unread,
+    Read,
+}
+
+impl ReadFilter {
+    /// All → Unread → Read → All.
+    pub fn next(self) -> Self {
+        match self {
+            Self::All => Self::Unread,
+            Self::Unread => Self::Read,
+            Self::Read => Self::All,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::Unread => "unread",
+            Self::Read => "read",
+        }
+    }
+
+    /// Whether an item with the given read state passes the filter.
+    pub fn matches(self, read: bool) -> bool {
+        match self {
+            Self::All => true,
+            Self::Unread => !read,
+            Self::Read => read,
+        }
+    }
+}
+
+/// Format used when copying an item.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CitationStyle {
+    /// `Title <link>`
+    Plain,
+    /// `[Title](link)`
+    Markdown,
+    /// `Title — Author (date) <link>`
+    #[default]
+    Citation,
+    /// `ui.citation_template`
+    Custom,
+}
+
+impl CitationStyle {
+    /// Plain → Markdown → Citation → Custom → Plain, skipping Custom when
+    /// no template is configured.
+    pub fn next(self, has_custom: bool) -> Self {
+        match self {
+            Self::Plain => Self::Markdown,
+            Self::Markdown => Self::Citation,
+            Self::Citation if has_custom => Self::Custom,
+            Self::Citation | Self::Custom => Self::Plain,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Plain => "plain",
+            Self::Markdown => "markdown",
+            Self::Citation => "citation",
+            Self::Custom => "custom",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum SortOrder {
+    #[default]
     Date,
     Title,
     Channel,
 }
 
-impl Default for SortOrder {
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Refresh {
+    #[serde(default = "default_interval")]
+    pub interval_secs: u64,
+    /// Consecutive failures after which a feed is disabled; 0 never disables.
+    #[serde(default = "default_max_failures")]
+    pub max_failures: u32,
+    /// Try to repair feeds that fail to parse instead of rejecting them.
+    #[serde(default)]
+    pub lenient_parsing: bool,
+    /// Leave out entries without a link instead of listing items that open
+    /// nothing.
+    #[serde(default)]
+    pub skip_linkless_entries: bool,
+}
+
+impl Default for Refresh {
     fn default() -> Self {
-        SortOrder::Date
+        Self {
+            interval_secs: default_interval(),
+            max_failures: DEFAULT_MAX_FAILURES,
+            lenient_parsing: false,
+            skip_linkless_entries: false,
+        }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
-pub struct Refresh {
-    #[serde(default = "default_interval")]
-    pub interval_secs: u64,
+/// Limits on how hard a refresh pass hits the network.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Net {
+    /// Feeds fetched at the same time across all hosts.
+    #[serde(default = "default_max_concurrent")]
+    pub max_concurrent: usize,
+    /// Feeds fetched at the same time from one host.
+    #[serde(default = "default_per_host")]
+    pub per_host: usize,
+    /// Pause after each request before the same slot fetches from that host
+    /// again, in milliseconds.
+    #[serde(default)]
+    pub per_host_delay_ms: u64,
+}
+
+impl Default for Net {
+    fn default() -> Self {
+        Self {
+            max_concurrent: default_max_concurrent(),
+            per_host: default_per_host(),
+            per_host_delay_ms: 0,
+        }
+    }
+}
+
+const fn default_max_concurrent() -> usize {
+    8
+}
+
+const fn default_per_host() -> usize {
+    2
 }
 
+/// Default number of consecutive failures before a feed is disabled.
+pub const DEFAULT_MAX_FAILURES: u32 = 10;
+
 const fn default_interval() -> u64 {
     900
 }
 
+const fn default_max_failures() -> u32 {
+    DEFAULT_MAX_FAILURES
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+/// Tokens understood by `item_format`.
+pub const ITEM_FORMAT_TOKENS: &[&str] = &["badge", "date", "title", "feed"];
+
+fn default_item_format() -> String {
+    "{badge} {date} {title}".into()
+}
+
+const fn default_unread_summary_groups() -> usize {
+    3
+}
+
+fn default_unread_glyph() -> String {
+    "●".into()
+}
+
+fn default_read_glyph() -> String {
+    " ".into()
+}
+
+fn default_status_format() -> String {
+    "last refresh: {last_refresh} | new items: {new_items}".into()
+}
+
 impl Default for Ui {
     fn default() -> Self {
         Self {
             theme: Theme::Dark,
-            unread_only: true,
+            read_filter: Some(ReadFilter::Unread),
+            unread_only: false,
             sort: SortOrder::Date,
+            sort_reverse: false,
+            status_format: default_status_format(),
+            wrap_titles: false,
+            restore_session: true,
+            item_format: default_item_format(),
+            item_list_density: ItemDensity::default(),
+            unread_glyph: default_unread_glyph(),
+            read_glyph: default_read_glyph(),
+            color_by_age: false,
+            tree_view: false,
+            wrap_navigation: false,
+            unread_summary: true,
+            unread_summary_groups: default_unread_summary_groups(),
+            confirm_delete: true,
+            confirm_bulk_open: true,
+            confirm_quit_with_queue: true,
+            default_group: None,
+            show_preview: true,
+            preview_max_chars: None,
+            prefer_full_content: false,
+            hide_read_after_days: None,
+            collapse_similar_titles: None,
+            page_size: None,
+            layout: PaneLayout::default(),
+            queue_open_marks_read: true,
+            queue_open_clears: true,
+            citation_style: CitationStyle::default(),
+            citation_template: None,
+        }
+    }
+}
+
+impl Ui {
+    /// The active read filter.
+    pub fn read_filter(&self) -> ReadFilter {
+        self.read_filter.unwrap_or_default()
+    }
+
+    /// Template for the active citation style. The custom style falls back
+    /// to the citation preset when no template is configured.
+    pub fn citation_template(&self) -> &str {
+        match (self.citation_style, &self.citation_template) {
+            (CitationStyle::Plain, _) => "{title} <{link}>",
+            (CitationStyle::Markdown, _) => "[{title}]({link})",
+            (CitationStyle::Custom, Some(template)) => template,
+            (CitationStyle::Citation | CitationStyle::Custom, _) => {
+                "{title} — {author} ({date}) <{link}>"
+            }
         }
     }
+
+    /// The item row template, or the default if the configured one uses
+    /// unknown tokens, has unbalanced braces or omits `{title}`.
+    pub fn item_format(&self) -> std::borrow::Cow<'_, str> {
+        if valid_item_format(&self.item_format) {
+            self.item_format.as_str().into()
+        } else {
+            default_item_format().into()
+        }
+    }
+}
+
+fn valid_item_format(template: &str) -> bool {
+    let mut rest = template;
+    let mut has_title = false;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            return false;
+        };
+        let token = &rest[start + 1..start + end];
+        if !ITEM_FORMAT_TOKENS.contains(&token) {
+            return false;
+        }
+        has_title |= token == "title";
+        rest = &rest[start + end + 1..];
+    }
+    has_title && !rest.contains('}')
 }
 
 impl Default for Keys {
@@ -89,8 +710,8 @@ impl Default for Keys {
 
 impl Config {
     fn path() -> std::path::PathBuf {
-        BaseDirs::new()
-            .map(|d| d.config_dir().join("rssq").join("config.toml"))
+        config_dir()
+            .map(|d| d.join("config.toml"))
             .unwrap_or_else(|| std::path::PathBuf::from("config.toml"))
     }
 
@@ -98,8 +719,12 @@ impl Config {
     pub fn load() -> std::io::Result<Self> {
         let path = Self::path();
         if let Ok(data) = std::fs::read_to_string(&path) {
-            toml::from_str(&data)
-                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+            let mut cfg: Self = toml::from_str(&data)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            if cfg.ui.read_filter.is_none() && cfg.ui.unread_only {
+                cfg.ui.read_filter = Some(ReadFilter::Unread);
+            }
+            Ok(cfg)
         } else {
             let cfg = Self::default();
             cfg.save()?;
@@ -107,8 +732,11 @@ impl Config {
         }
     }
 
-    /// Persist configuration to disk.
+    /// Persist configuration to disk. Does nothing in read-only mode.
     pub fn save(&self) -> std::io::Result<()> {
+        if read_only() {
+            return Ok(());
+        }
         let path = Self::path();
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
@@ -117,3 +745,63 @@ impl Config {
         std::fs::write(path, data)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Empty directory under the system temp dir, unique to `name`.
+    fn temp_parent(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mrss-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn legacy_dir_is_moved() {
+        let parent = temp_parent("legacy-only");
+        fs::create_dir(parent.join(LEGACY_APP_DIR)).unwrap();
+        fs::write(parent.join(LEGACY_APP_DIR).join("db.json"), "[]").unwrap();
+
+        let moved = migrate_legacy_dir(&parent).unwrap();
+        assert_eq!(moved, Some(parent.join(APP_DIR)));
+        assert!(!parent.join(LEGACY_APP_DIR).exists());
+        assert_eq!(
+            fs::read_to_string(parent.join(APP_DIR).join("db.json")).unwrap(),
+            "[]"
+        );
+        // Nothing is left to move the next time.
+        assert_eq!(migrate_legacy_dir(&parent).unwrap(), None);
+        fs::remove_dir_all(parent).unwrap();
+    }
+
+    #[test]
+    fn new_dir_alone_is_left_alone() {
+        let parent = temp_parent("new-only");
+        fs::create_dir(parent.join(APP_DIR)).unwrap();
+
+        assert_eq!(migrate_legacy_dir(&parent).unwrap(), None);
+        assert!(parent.join(APP_DIR).is_dir());
+        assert!(!parent.join(LEGACY_APP_DIR).exists());
+        fs::remove_dir_all(parent).unwrap();
+    }
+
+    #[test]
+    fn existing_new_dir_is_not_overwritten() {
+        let parent = temp_parent("both");
+        fs::create_dir(parent.join(LEGACY_APP_DIR)).unwrap();
+        fs::create_dir(parent.join(APP_DIR)).unwrap();
+        fs::write(parent.join(LEGACY_APP_DIR).join("db.json"), "old").unwrap();
+        fs::write(parent.join(APP_DIR).join("db.json"), "new").unwrap();
+
+        assert_eq!(migrate_legacy_dir(&parent).unwrap(), None);
+        assert_eq!(
+            fs::read_to_string(parent.join(APP_DIR).join("db.json")).unwrap(),
+            "new"
+        );
+        assert!(parent.join(LEGACY_APP_DIR).join("db.json").exists());
+        fs::remove_dir_all(parent).unwrap();
+    }
+}
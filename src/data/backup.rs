@@ -0,0 +1,182 @@
+//! Portable JSON backups of the whole database.
+//!
+//! The format is the database's own serialization, so a backup can also be
+//! dropped in place of `db.json`.
+
+use std::{collections::HashMap, fmt, fs, io, path::Path};
+
+use super::{Group, LEGACY_ID_LEN, find_or_create_group};
+use crate::net::same_feed_url;
+
+/// What an import changed.
+#[derive(Debug, Default)]
+pub struct BackupSummary {
+    pub feeds_added: usize,
+    pub feeds_merged: usize,
+    pub items_added: usize,
+}
+
+impl fmt::Display for BackupSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "added {} feeds, merged {} existing, added {} items",
+            self.feeds_added, self.feeds_merged, self.items_added
+        )
+    }
+}
+
+/// Write every group, feed and item to `path`.
+pub fn export_json(groups: &[Group], path: &Path) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(groups).map_err(io::Error::other)?;
+    fs::write(path, json)
+}
+
+/// Merge the backup at `path` into `groups`.
+///
+/// Feeds not subscribed to yet are added with their items to the group
+/// they were in. For feeds already present, missing items are added and an
+/// item read or starred on either side stays so; nothing is ever removed.
+/// Items are matched by id, or by its first [`LEGACY_ID_LEN`] characters so
+/// backups from before full-length ids still line up. Queue membership is
+/// not imported.
+pub fn import_json(
+    groups: &mut Vec<Group>,
+    path: &Path,
+) -> Result<BackupSummary, Box<dyn std::error::Error>> {
+    let backup: Vec<Group> = serde_json::from_str(&fs::read_to_string(path)?)?;
+    let mut summary = BackupSummary::default();
+    for source in backup {
+        for mut feed in source.feeds {
+            for item in &mut feed.items {
+                item.queued = false;
+            }
+            let existing = groups.iter_mut().find_map(|g| {
+                g.feeds
+                    .iter_mut()
+                    .find(|f| same_feed_url(&f.url, &feed.url))
+            });
+            let Some(existing) = existing else {
+                let g = find_or_create_group(groups, &source.name);
+                summary.feeds_added += 1;
+                summary.items_added += feed.items.len();
+                groups[g].feeds.push(feed);
+                continue;
+            };
+            summary.feeds_merged += 1;
+            // Full ids first, so none is shadowed by another item's prefix.
+            let mut index: HashMap<String, usize> = HashMap::new();
+            for (i, item) in existing.items.iter().enumerate() {
+                index.insert(item.id.clone(), i);
+            }
+            for (i, item) in existing.items.iter().enumerate() {
+                if let Some(legacy) = item.id.get(..LEGACY_ID_LEN) {
+                    index.entry(legacy.to_string()).or_insert(i);
+                }
+            }
+            for item in feed.items {
+                let known = index.get(&item.id).or_else(|| {
+                    item.id
+                        .get(..LEGACY_ID_LEN)
+                        .and_then(|legacy| index.get(legacy))
+                });
+                match known {
+                    Some(&i) => {
+                        let mine = &mut existing.items[i];
+                        mine.set_read(mine.read || item.read);
+                        mine.set_starred(mine.starred || item.starred);
+                    }
+                    None => {
+                        summary.items_added += 1;
+                        existing.items.push(item);
+                    }
+                }
+            }
+            existing.items.sort_by(super::newest_first);
+        }
+    }
+    for group in groups.iter_mut() {
+        group.update_unread();
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{Feed, Item};
+
+    fn item(id: &str, read: bool, starred: bool) -> Item {
+        let mut item: Item = serde_json::from_value(serde_json::json!({
+            "id": id,
+            "title": id,
+            "link": format!("https://example.com/{id}"),
+            "timestamp": 1_700_000_000,
+        }))
+        .unwrap();
+        item.read = read;
+        item.starred = starred;
+        item
+    }
+
+    fn group(name: &str, url: &str, items: Vec<Item>) -> Group {
+        Group {
+            name: name.into(),
+            feeds: vec![Feed {
+                url: url.into(),
+                items,
+                ..Feed::default()
+            }],
+            ..Group::default()
+        }
+    }
+
+    #[test]
+    fn export_then_import_merges_feeds_and_flags() {
+        let full = Item::gen_id(Some("c"), "https://example.com/c");
+        let mut backup = vec![group(
+            "News",
+            "https://example.com/world.xml",
+            vec![
+                item("a", true, false),
+                // Written before ids were full length.
+                item(&full[..LEGACY_ID_LEN], false, true),
+                item("new", false, false),
+            ],
+        )];
+        backup.push(group(
+            "Tech",
+            "https://example.com/tech.xml",
+            vec![item("t", false, false)],
+        ));
+        let path =
+            std::env::temp_dir().join(format!("mrss-test-backup-{}.json", std::process::id()));
+        export_json(&backup, &path).unwrap();
+
+        let mut groups = vec![group(
+            "news",
+            "HTTP://Example.com/world.xml/",
+            vec![item("a", false, false), item(&full, false, false)],
+        )];
+        let summary = import_json(&mut groups, &path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(
+            (
+                summary.feeds_added,
+                summary.feeds_merged,
+                summary.items_added
+            ),
+            (1, 1, 2)
+        );
+        assert_eq!(groups.len(), 2);
+        let world = &groups[0].feeds[0];
+        assert_eq!(world.items.len(), 3);
+        let find = |id: &str| world.items.iter().find(|i| i.id == id).unwrap();
+        assert!(find("a").read);
+        assert!(find(&full).starred && !find(&full).read);
+        assert!(!find("new").read);
+        assert_eq!(groups[0].unread_count, 2);
+        assert_eq!(groups[1].name, "Tech");
+    }
+}
@@ -2,14 +2,38 @@
 
 //! Data models and persistence layer.
 
-use std::{fs, io, path::PathBuf};
+pub mod backup;
+pub mod opml;
+pub mod sync;
+
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    sync::{
+        OnceLock,
+        atomic::{AtomicBool, Ordering as AtomicOrdering},
+    },
+};
 
-use directories::BaseDirs;
 use feed_rs::model as feedmodel;
-use log::error;
+use log::{error, warn};
+
+use crate::config::{self, SortOrder};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use sha1::{Digest, Sha1};
-use std::collections::HashMap;
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
+
+/// Length of the truncated ids written before full hashes were used.
+pub const LEGACY_ID_LEN: usize = 16;
+
+/// Longest full content kept per item, in bytes, so feeds that embed whole
+/// articles don't bloat the database.
+pub const MAX_CONTENT_LEN: usize = 64 * 1024;
 
 /// RSS item.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,22 +43,106 @@ pub struct Item {
     pub link: String,
     #[serde(default)]
     pub desc: String,
+    /// Full article body when the feed includes one besides the summary,
+    /// cut to [`MAX_CONTENT_LEN`] bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
     pub timestamp: i64,
     #[serde(default)]
     pub read: bool,
     #[serde(default)]
     pub queued: bool,
+    /// Kept for later; unlike the queue, starring is permanent.
+    #[serde(default)]
+    pub starred: bool,
+    /// Links found in the item body plus any enclosures, as `(text, href)`.
+    #[serde(default)]
+    pub links: Vec<(String, String)>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// Hash of title, link and description used to tell edits and re-issued
+    /// ids apart from genuinely new entries.
+    #[serde(default)]
+    pub content_hash: String,
+    /// Unix timestamp of the last change to `read`, `queued` or `starred`;
+    /// 0 if they were never changed. Used to merge read state across
+    /// machines. Change the flags through the setters to keep it current.
+    #[serde(default)]
+    pub flags_updated: i64,
+    /// Unix timestamp of when the item was marked read; `None` while unread
+    /// and for items read before this was recorded.
+    #[serde(default)]
+    pub read_at: Option<i64>,
+    /// Dismissed with `x`: left out of every list and of unread counts, but
+    /// its read state is kept.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hidden: bool,
+    /// The feed dated the item in the future; `timestamp` was capped to when
+    /// it was first fetched so it doesn't stay pinned to the top.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub future_dated: bool,
 }
 
 impl Item {
-    /// Generate a stable 16-hex identifier from entry id or link.
+    /// The full content when `full` is set and the feed provided one,
+    /// otherwise the summary.
+    pub fn body(&self, full: bool) -> &str {
+        match &self.content {
+            Some(content) if full => content,
+            _ => &self.desc,
+        }
+    }
+
+    /// Whether the item still waits to be read: unread and not hidden.
+    pub fn is_unread(&self) -> bool {
+        !self.read && !self.hidden
+    }
+
+    pub fn set_read(&mut self, read: bool) {
+        if self.read != read {
+            let now = chrono::Utc::now().timestamp();
+            self.read = read;
+            self.read_at = read.then_some(now);
+            self.flags_updated = now;
+        }
+    }
+
+    pub fn set_queued(&mut self, queued: bool) {
+        if self.queued != queued {
+            self.queued = queued;
+            self.flags_updated = chrono::Utc::now().timestamp();
+        }
+    }
+
+    pub fn set_starred(&mut self, starred: bool) {
+        if self.starred != starred {
+            self.starred = starred;
+            self.flags_updated = chrono::Utc::now().timestamp();
+        }
+    }
+
+    /// Generate a stable 40-hex identifier from entry id or link.
+    ///
+    /// Ids generated by older versions are the first [`LEGACY_ID_LEN`]
+    /// characters of the same hash.
     pub fn gen_id(id: Option<&str>, link: &str) -> String {
         let source = id.unwrap_or(link);
         let mut hasher = Sha1::new();
         hasher.update(source.as_bytes());
         let hash = hasher.finalize();
-        let hex = format!("{:x}", hash);
-        hex[..16].to_string()
+        format!("{:x}", hash)
+    }
+
+    /// Hash the user-visible content of an entry.
+    pub fn hash_content(title: &str, link: &str, desc: &str) -> String {
+        let mut hasher = Sha1::new();
+        for part in [title, link, desc] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        format!("{:x}", hasher.finalize())
     }
 }
 
@@ -49,6 +157,75 @@ pub struct Feed {
     pub etag: Option<String>,
     #[serde(default)]
     pub last_modified: Option<String>,
+    /// Sort order override for this feed's items.
+    #[serde(default)]
+    pub sort: Option<SortOrder>,
+    /// Unix timestamp before which the server asked not to be polled again.
+    #[serde(default)]
+    pub next_fetch: Option<i64>,
+    /// Unix timestamp until which the server rate-limited us.
+    #[serde(default)]
+    pub rate_limited_until: Option<i64>,
+    /// Unix timestamp of the last successful fetch.
+    #[serde(default)]
+    pub last_fetch: Option<i64>,
+    /// Number of consecutive failed fetches.
+    #[serde(default)]
+    pub error_streak: u32,
+    /// Why the last fetch failed; cleared by a successful one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// HTTP status of the last fetch, if the server answered.
+    #[serde(default)]
+    pub last_status: Option<u16>,
+    /// Whether the last fetch was answered with `304 Not Modified`.
+    #[serde(default)]
+    pub not_modified: bool,
+    /// Set after too many consecutive failures; skipped when refreshing.
+    #[serde(default)]
+    pub disabled: bool,
+    /// Pinned feeds are kept at the top of their group.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Whether the last fetched document needed repairs before it parsed.
+    #[serde(default)]
+    pub lenient_parse: bool,
+    /// Timestamp of the newest item when the user last left this feed; items
+    /// after it are shown above a "new" divider.
+    #[serde(default)]
+    pub last_seen_timestamp: Option<i64>,
+    /// Set when the last fetched document had no entries although the feed
+    /// had items; the old items are kept.
+    #[serde(default)]
+    pub empty_fetch: bool,
+    /// Polling interval in seconds the feed suggests via `<ttl>` or
+    /// `sy:updatePeriod`, if any.
+    #[serde(default)]
+    pub update_interval: Option<i64>,
+    /// Per-feed override of `opener.background`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_in_background: Option<bool>,
+    /// Override of `[ui] prefer_full_content`, toggled with `C`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefer_full_content: Option<bool>,
+    /// Unsubscribed with `D`: never fetched again, but its items are kept
+    /// for reading and search.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unsubscribed: bool,
+    /// Entries in the last fetched document without a link; they open
+    /// nothing.
+    #[serde(default)]
+    pub missing_links: usize,
+    /// Entries in the last fetched document without a publication or update
+    /// date; they sort as the oldest.
+    #[serde(default)]
+    pub missing_dates: usize,
+    /// List items in sections by their first category; toggled with `G`.
+    #[serde(default)]
+    pub group_by_category: bool,
+    /// The site the feed belongs to, from its channel `<link>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub home_url: Option<String>,
 }
 
 /// Grouping of feeds.
@@ -57,123 +234,855 @@ pub struct Group {
     pub name: String,
     #[serde(default)]
     pub feeds: Vec<Feed>,
+    /// Cached sum of the feeds' [`Feed::unread_count`]. Anything that
+    /// changes an item's read state or a group's feeds must call
+    /// [`Group::update_unread`] afterwards.
     #[serde(default)]
     pub unread_count: usize,
+    /// Sort order override for feeds in this group without their own.
+    #[serde(default)]
+    pub sort: Option<SortOrder>,
+    /// Whether the group's feeds are shown in the tree view. Not persisted.
+    #[serde(skip)]
+    pub expanded: bool,
 }
 
 impl Feed {
+    /// Whether the feed may be fetched at `now` (a Unix timestamp).
+    pub fn is_due(&self, now: i64) -> bool {
+        !self.disabled
+            && !self.unsubscribed
+            && self.next_fetch.is_none_or(|t| now >= t)
+            && self.rate_limited_until.is_none_or(|t| now >= t)
+    }
+
+    /// Number of unread items, not counting hidden ones.
+    pub fn unread_count(&self) -> usize {
+        self.items.iter().filter(|i| i.is_unread()).count()
+    }
+
+    /// Number of items, read or not.
+    pub fn total_count(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Fetch the feed from `url` from now on. Caching and health data belong
+    /// to the old location, so they are reset; items are kept.
+    pub fn set_url(&mut self, url: String) {
+        self.url = url;
+        self.etag = None;
+        self.last_modified = None;
+        self.next_fetch = None;
+        self.rate_limited_until = None;
+        self.error_streak = 0;
+        self.last_error = None;
+        self.last_status = None;
+        self.not_modified = false;
+        self.empty_fetch = false;
+        self.disabled = false;
+    }
+
+    /// Record that every current item has been seen.
+    pub fn mark_seen(&mut self) {
+        if let Some(newest) = self.items.iter().map(|i| i.timestamp).max() {
+            self.last_seen_timestamp = Some(newest);
+        }
+    }
+
     /// Merge parsed feed data into this feed, preserving read/queued flags.
-    pub fn merge_items(&mut self, parsed: feedmodel::Feed) {
+    ///
+    /// Entries are matched to existing items by id, or failing that by content
+    /// hash so feeds that re-issue ids don't duplicate items. Returns the
+    /// number of genuinely new items; edited entries are updated in place and
+    /// not counted. Entries the feed no longer lists are dropped.
+    ///
+    /// The item list is kept sorted without re-sorting it: new items are
+    /// inserted where they belong, and the list is only sorted again when an
+    /// entry's date changed, undated entries moved or many items arrived at
+    /// once. Undated items follow the order of the feed.
+    ///
+    /// A document without entries never removes existing items: it sets
+    /// [`Feed::empty_fetch`] instead, as it usually means the server is
+    /// broken rather than that every item was withdrawn.
+    ///
+    /// Entries without a link or date are counted in
+    /// [`Feed::missing_links`] and [`Feed::missing_dates`]; with
+    /// `skip_linkless` those without a link are left out.
+    pub fn merge_items(&mut self, parsed: feedmodel::Feed, skip_linkless: bool) -> usize {
         // Update title if present
         if let Some(title) = parsed.title {
             self.title = title.content;
         }
+        // Atom also lists `self` and other relations; the homepage is the
+        // alternate (or untyped, in RSS) link.
+        if let Some(link) = parsed
+            .links
+            .into_iter()
+            .find(|l| l.rel.as_deref().is_none_or(|rel| rel == "alternate"))
+        {
+            self.home_url = Some(link.href);
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let mut entries = parsed.entries;
+        self.missing_links = entries.iter().filter(|e| e.links.is_empty()).count();
+        self.missing_dates = entries.iter().filter(|e| entry_timestamp(e) == 0).count();
+        if self.missing_links > 0 || self.missing_dates > 0 {
+            warn!(
+                "{}: {} entries missing links, {} missing dates",
+                self.url, self.missing_links, self.missing_dates
+            );
+        }
+        if skip_linkless {
+            entries.retain(|e| !e.links.is_empty());
+        }
+
+        self.empty_fetch = entries.is_empty() && !self.items.is_empty();
+        if self.empty_fetch {
+            warn!(
+                "{} returned no entries; keeping {} items",
+                self.url,
+                self.items.len()
+            );
+            return 0;
+        }
 
-        // Map existing items by id to preserve state
-        let existing: HashMap<String, Item> = self
+        // Index existing items by position so nothing is cloned; matches are
+        // resolved first and applied once the indexes are dropped.
+        let by_id: HashMap<&str, usize> = self
             .items
             .iter()
-            .cloned()
-            .map(|i| (i.id.clone(), i))
+            .enumerate()
+            .map(|(i, item)| (item.id.as_str(), i))
             .collect();
+        let by_hash: HashMap<&str, usize> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| !item.content_hash.is_empty())
+            .map(|(i, item)| (item.content_hash.as_str(), i))
+            .collect();
+        let mut used_ids = HashSet::new();
+        // Existing items still listed by the feed; the rest are dropped.
+        let mut kept = vec![false; self.items.len()];
+        // Parsed items with the existing item they replace, if any, and
+        // whether they take over its id.
+        let mut matched = Vec::with_capacity(entries.len());
 
-        let mut new_items = Vec::new();
-        for entry in parsed.entries {
+        for entry in entries {
             let link = entry
                 .links
                 .first()
                 .map(|l| l.href.clone())
                 .unwrap_or_default();
-            let id = Item::gen_id(Some(&entry.id), &link);
+            let base_id = Item::gen_id(Some(&entry.id), &link);
+            let mut id = base_id.clone();
+            if used_ids.contains(&id) {
+                // Another entry in this feed produced the same id; mix in the
+                // link, then fall back to a counter.
+                id = Item::gen_id(Some(&format!("{}\n{}", entry.id, link)), &link);
+                let stem = id.clone();
+                let mut n = 1;
+                while used_ids.contains(&id) {
+                    n += 1;
+                    id = format!("{stem}-{n}");
+                }
+            }
+            used_ids.insert(id.clone());
 
+            let mut links = Vec::new();
+            if let Some(summary) = &entry.summary {
+                links.extend(extract_links(&summary.content));
+            }
+            if let Some(body) = entry.content.as_ref().and_then(|c| c.body.as_deref()) {
+                links.extend(extract_links(body));
+            }
+            for l in entry
+                .links
+                .iter()
+                .filter(|l| l.rel.as_deref() == Some("enclosure"))
+            {
+                let text = l.title.clone().unwrap_or_else(|| "enclosure".into());
+                links.push((text, l.href.clone()));
+            }
+            for media in &entry.media {
+                for content in &media.content {
+                    if let Some(url) = &content.url {
+                        links.push(("enclosure".into(), url.to_string()));
+                    }
+                }
+            }
+            let mut seen = HashSet::new();
+            links.retain(|(_, href)| seen.insert(href.clone()));
+
+            let timestamp = entry_timestamp(&entry);
             let mut item = Item {
-                id: id.clone(),
-                title: entry
-                    .title
-                    .as_ref()
-                    .map(|t| t.content.clone())
-                    .unwrap_or_default(),
+                id,
+                title: entry.title.map(|t| t.content).unwrap_or_default(),
                 link,
-                desc: entry
-                    .summary
-                    .as_ref()
-                    .map(|s| s.content.clone())
-                    .unwrap_or_default(),
-                timestamp: entry
-                    .published
-                    .or(entry.updated)
-                    .map(|d| d.timestamp())
-                    .unwrap_or_default(),
+                desc: entry.summary.map(|s| s.content).unwrap_or_default(),
+                content: entry
+                    .content
+                    .and_then(|c| c.body)
+                    .filter(|body| !body.trim().is_empty())
+                    .map(|mut body| {
+                        if body.len() > MAX_CONTENT_LEN {
+                            let end = (0..=MAX_CONTENT_LEN)
+                                .rev()
+                                .find(|&i| body.is_char_boundary(i))
+                                .unwrap_or_default();
+                            body.truncate(end);
+                        }
+                        body
+                    }),
+                timestamp,
                 read: false,
                 queued: false,
+                starred: false,
+                links,
+                author: entry.authors.into_iter().next().map(|a| a.name),
+                categories: entry
+                    .categories
+                    .into_iter()
+                    .map(|c| c.label.unwrap_or(c.term))
+                    .collect(),
+                content_hash: String::new(),
+                flags_updated: 0,
+                read_at: None,
+                hidden: false,
+                future_dated: false,
             };
+            if item.timestamp > now {
+                item.timestamp = now;
+                item.future_dated = true;
+            }
+            item.content_hash = Item::hash_content(&item.title, &item.link, &item.desc);
+
+            let known = by_id
+                .get(item.id.as_str())
+                .or_else(|| {
+                    (item.id == base_id)
+                        .then(|| by_id.get(&item.id[..LEGACY_ID_LEN]))
+                        .flatten()
+                })
+                .copied()
+                .filter(|&i| !kept[i]);
+            let slot = match known {
+                Some(i) => Some((i, false)),
+                None => by_hash
+                    .get(item.content_hash.as_str())
+                    .copied()
+                    .filter(|&i| !kept[i] && !used_ids.contains(&self.items[i].id))
+                    .map(|i| {
+                        used_ids.insert(self.items[i].id.clone());
+                        (i, true)
+                    }),
+            };
+            if let Some((i, _)) = slot {
+                kept[i] = true;
+            }
+            matched.push((item, slot));
+        }
 
-            if let Some(old) = existing.get(&id) {
-                item.read = old.read;
-                item.queued = old.queued;
+        let mut added = 0;
+        // Whether a kept item's date changed, which may reorder the list.
+        let mut resort = false;
+        let mut new_items = Vec::new();
+        // Position of each item in the feed, the order undated items keep.
+        let mut feed_order = HashMap::with_capacity(matched.len());
+        for (n, (mut item, slot)) in matched.into_iter().enumerate() {
+            let Some((i, reuse_id)) = slot else {
+                added += 1;
+                feed_order.insert(item.id.clone(), n);
+                new_items.push(item);
+                continue;
+            };
+            let old = &mut self.items[i];
+            if reuse_id {
+                // Same content under a new id: keep the id we already know.
+                item.id = std::mem::take(&mut old.id);
+            }
+            if item.future_dated && old.future_dated {
+                // Still in the future: keep the time it was first seen.
+                item.timestamp = old.timestamp;
             }
+            resort |= old.timestamp != item.timestamp;
+            item.read = old.read;
+            item.queued = old.queued;
+            item.starred = old.starred;
+            item.flags_updated = old.flags_updated;
+            item.read_at = old.read_at;
+            item.hidden = old.hidden;
+            feed_order.insert(item.id.clone(), n);
+            *old = item;
+        }
 
-            new_items.push(item);
+        let mut kept = kept.into_iter();
+        self.items.retain(|_| kept.next().unwrap_or(false));
+        // Undated items tie under `newest_first`; break that by feed order.
+        let order = |a: &Item, b: &Item| {
+            newest_first(a, b).then_with(|| feed_order.get(&a.id).cmp(&feed_order.get(&b.id)))
+        };
+        // The feed may also have moved undated items it listed before.
+        resort |= !self
+            .items
+            .is_sorted_by(|a, b| order(a, b) != Ordering::Greater);
+        if resort || new_items.len() > MERGE_INSERT_LIMIT {
+            self.items.append(&mut new_items);
+            self.items.sort_by(order);
+        } else {
+            for item in new_items {
+                let pos = self
+                    .items
+                    .partition_point(|other| order(other, &item) != Ordering::Greater);
+                self.items.insert(pos, item);
+            }
         }
+        added
+    }
+}
+
+/// Publication (or else update) time of an entry, or 0 when it has none.
+/// Dates at or before the Unix epoch are placeholders and count as none, so
+/// the item sorts with the undated ones instead of as the oldest.
+fn entry_timestamp(entry: &feedmodel::Entry) -> i64 {
+    entry
+        .published
+        .or(entry.updated)
+        .map_or(0, |d| d.timestamp().max(0))
+}
+
+/// Number of new items merged by inserting each at its sorted position; more
+/// than this and the whole list is re-sorted instead.
+const MERGE_INSERT_LIMIT: usize = 16;
 
-        // Newest first by timestamp
-        new_items.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-        self.items = new_items;
+/// Order items newest first, breaking ties by id so the order is
+/// deterministic. Undated items (timestamp 0) sort after dated ones and, as
+/// the sort is stable, keep the order the feed listed them in.
+pub fn newest_first(a: &Item, b: &Item) -> Ordering {
+    match (a.timestamp == 0, b.timestamp == 0) {
+        (false, false) => b.timestamp.cmp(&a.timestamp).then_with(|| a.id.cmp(&b.id)),
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
     }
 }
 
 impl Group {
+    /// Move pinned feeds to the top, otherwise keeping the existing order.
+    pub fn sort_pinned(&mut self) {
+        self.feeds.sort_by_key(|f| !f.pinned);
+    }
+
     /// Recalculate unread count for the group.
     pub fn update_unread(&mut self) {
-        self.unread_count = self
-            .feeds
-            .iter()
-            .map(|f| f.items.iter().filter(|i| !i.read).count())
-            .sum();
+        self.unread_count = self.feeds.iter().map(Feed::unread_count).sum();
+    }
+
+    /// Number of feeds in the group.
+    pub fn feed_count(&self) -> usize {
+        self.feeds.len()
+    }
+}
+
+/// Whether two group names refer to the same group: names are compared
+/// ignoring case and surrounding whitespace, so "News" and "news " can't
+/// coexist.
+pub fn same_group_name(a: &str, b: &str) -> bool {
+    a.trim().to_lowercase() == b.trim().to_lowercase()
+}
+
+/// Index of the group called `name` (see [`same_group_name`]), if any,
+/// ignoring the group at `except`.
+pub fn find_group(groups: &[Group], name: &str, except: Option<usize>) -> Option<usize> {
+    groups
+        .iter()
+        .enumerate()
+        .position(|(i, g)| Some(i) != except && same_group_name(&g.name, name))
+}
+
+/// Index of the group called `name`, appending an empty one if there is
+/// none.
+pub fn find_or_create_group(groups: &mut Vec<Group>, name: &str) -> usize {
+    if let Some(i) = find_group(groups, name, None) {
+        return i;
     }
+    groups.push(Group {
+        name: name.to_string(),
+        ..Group::default()
+    });
+    groups.len() - 1
+}
+
+/// Extract `(text, href)` pairs for every `<a href>` in an HTML fragment.
+fn extract_links(html: &str) -> Vec<(String, String)> {
+    static ANCHOR: OnceLock<Regex> = OnceLock::new();
+    static TAG: OnceLock<Regex> = OnceLock::new();
+    let anchor = ANCHOR.get_or_init(|| {
+        Regex::new(r#"(?is)<a\s[^>]*?href\s*=\s*["']([^"']+)["'][^>]*>(.*?)</a>"#).unwrap()
+    });
+    let tag = TAG.get_or_init(|| Regex::new(r"(?s)<[^>]*>").unwrap());
+    anchor
+        .captures_iter(html)
+        .map(|c| {
+            let href = c[1].trim().to_string();
+            let text = tag.replace_all(&c[2], "").trim().to_string();
+            let text = if text.is_empty() { href.clone() } else { text };
+            (text, href)
+        })
+        .collect()
+}
+
+/// Total unread items across all groups, using each group's cached count
+/// (see [`Group::unread_count`]).
+pub fn total_unread(groups: &[Group]) -> usize {
+    groups.iter().map(|g| g.unread_count).sum()
+}
+
+/// Whether [`save_db`] writes `db.json.gz` rather than `db.json`.
+static COMPRESS_DB: AtomicBool = AtomicBool::new(false);
+
+/// First bytes of every gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Choose whether the database is saved gzip-compressed (see
+/// [`config::Storage::compress_db`]). Loading detects the format either way.
+pub fn set_compress_db(compress: bool) {
+    COMPRESS_DB.store(compress, AtomicOrdering::Relaxed);
 }
 
 /// Resolve path to the database json file.
 fn db_path() -> Option<PathBuf> {
-    BaseDirs::new().map(|b| b.data_dir().join("rssq").join("db.json"))
+    config::data_dir().map(|d| d.join("db.json"))
 }
 
-/// Load the database from disk.
-pub fn load_db() -> io::Result<Vec<Group>> {
-    let path = db_path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "data dir"))?;
-    match fs::read_to_string(&path) {
-        Ok(content) => serde_json::from_str(&content).map_err(|e| {
-            error!("Failed to parse {}: {}", path.display(), e);
-            io::Error::new(io::ErrorKind::InvalidData, e)
-        }),
-        Err(e) if e.kind() == io::ErrorKind::NotFound => {
-            // No database yet.
-            Ok(Vec::new())
+/// Path of the compressed database kept instead of `json_path`.
+fn gz_path(json_path: &Path) -> PathBuf {
+    json_path.with_file_name("db.json.gz")
+}
+
+/// Resolve path to the UI session file stored next to the database.
+pub fn session_path() -> Option<PathBuf> {
+    db_path().map(|p| p.with_file_name("session.json"))
+}
+
+/// Why the database couldn't be loaded or saved.
+#[derive(Debug)]
+pub enum StoreError {
+    /// No data directory could be determined for this platform.
+    NoDataDir,
+    /// Reading, writing or creating `path` failed.
+    Io { path: PathBuf, source: io::Error },
+    /// The database at `path` isn't valid JSON for this version.
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    /// The database couldn't be serialized.
+    Serialize(serde_json::Error),
+}
+
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoDataDir => write!(f, "no data directory available"),
+            Self::Io { path, source } => write!(f, "cannot access {}: {}", path.display(), source),
+            Self::Parse { path, source } => {
+                write!(
+                    f,
+                    "{} is damaged or from a newer version: {}",
+                    path.display(),
+                    source
+                )
+            }
+            Self::Serialize(e) => write!(f, "cannot serialize the database: {}", e),
         }
-        Err(e) => {
-            error!("Failed to read {}: {}", path.display(), e);
-            Err(e)
+    }
+}
+
+impl std::error::Error for StoreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::NoDataDir => None,
+            Self::Io { source, .. } => Some(source),
+            Self::Parse { source, .. } => Some(source),
+            Self::Serialize(e) => Some(e),
         }
     }
 }
 
-/// Save the database to disk.
-pub fn save_db(db: &[Group]) -> io::Result<()> {
-    let path = db_path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "data dir"))?;
-    if let Some(parent) = path.parent()
-        && let Err(e) = fs::create_dir_all(parent)
+/// Load the database from disk. The file is parsed as it is read rather than
+/// loaded into memory first, which matters for large databases.
+pub fn load_db() -> Result<Vec<Group>, StoreError> {
+    let path = db_path().ok_or(StoreError::NoDataDir)?;
+    load_from(&path, COMPRESS_DB.load(AtomicOrdering::Relaxed))
+}
+
+/// Load `json_path` or its compressed sibling, trying the one matching
+/// `compress` first. Gzip data is recognised by its content, not its name.
+fn load_from(json_path: &Path, compress: bool) -> Result<Vec<Group>, StoreError> {
+    let gz_path = gz_path(json_path);
+    let candidates = if compress {
+        [gz_path, json_path.to_path_buf()]
+    } else {
+        [json_path.to_path_buf(), gz_path]
+    };
+    for path in candidates {
+        let file = match fs::File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(source) => {
+                error!("Failed to read {}: {}", path.display(), source);
+                return Err(StoreError::Io { path, source });
+            }
+        };
+        let mut reader = io::BufReader::new(file);
+        let gzip = match reader.fill_buf() {
+            Ok(head) => head.starts_with(&GZIP_MAGIC),
+            Err(source) => {
+                error!("Failed to read {}: {}", path.display(), source);
+                return Err(StoreError::Io { path, source });
+            }
+        };
+        let parsed = if gzip {
+            let decoder = flate2::bufread::GzDecoder::new(reader);
+            serde_json::from_reader(io::BufReader::new(decoder))
+        } else {
+            serde_json::from_reader(reader)
+        };
+        return parsed.map_err(|source| {
+            error!("Failed to parse {}: {}", path.display(), source);
+            StoreError::Parse { path, source }
+        });
+    }
+    // No database yet.
+    Ok(Vec::new())
+}
+
+/// Save the database to disk, compressed when enabled with
+/// [`set_compress_db`]. Does nothing in read-only mode.
+pub fn save_db(db: &[Group]) -> Result<(), StoreError> {
+    if config::read_only() {
+        return Ok(());
+    }
+    let path = db_path().ok_or(StoreError::NoDataDir)?;
+    save_to(&path, db, COMPRESS_DB.load(AtomicOrdering::Relaxed))
+}
+
+/// Write `db` to `json_path`, or gzip-compressed to its sibling when
+/// `compress` is set, then remove the file in the other format so a
+/// changed setting migrates the database.
+fn save_to(json_path: &Path, db: &[Group], compress: bool) -> Result<(), StoreError> {
+    if let Some(parent) = json_path.parent()
+        && let Err(source) = fs::create_dir_all(parent)
     {
-        error!("Failed to create {}: {}", parent.display(), e);
-        return Err(e);
-    }
-    match serde_json::to_string_pretty(db) {
-        Ok(json) => fs::write(&path, json).map_err(|e| {
-            error!("Failed to write {}: {}", path.display(), e);
-            e
-        }),
-        Err(e) => {
-            error!("Failed to serialize db: {}", e);
-            Err(io::Error::new(io::ErrorKind::InvalidData, e))
+        error!("Failed to create {}: {}", parent.display(), source);
+        return Err(StoreError::Io {
+            path: parent.to_path_buf(),
+            source,
+        });
+    }
+    let (path, stale) = if compress {
+        (gz_path(json_path), json_path.to_path_buf())
+    } else {
+        (json_path.to_path_buf(), gz_path(json_path))
+    };
+    let json = serde_json::to_vec_pretty(db).map_err(|e| {
+        error!("Failed to serialize db: {}", e);
+        StoreError::Serialize(e)
+    })?;
+    let written = if compress {
+        fs::File::create(&path).and_then(|file| {
+            let mut encoder = flate2::write::GzEncoder::new(
+                io::BufWriter::new(file),
+                flate2::Compression::default(),
+            );
+            encoder.write_all(&json)?;
+            encoder.finish()?.flush()
+        })
+    } else {
+        fs::write(&path, json)
+    };
+    if let Err(source) = written {
+        error!("Failed to write {}: {}", path.display(), source);
+        return Err(StoreError::Io { path, source });
+    }
+    match fs::remove_file(&stale) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => {
+            warn!("Failed to remove {}: {}", stale.display(), e);
         }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse an RSS document with one item per `(guid, link, pubDate)`.
+    fn rss(entries: &[(&str, &str, Option<&str>)]) -> feedmodel::Feed {
+        let items: String = entries
+            .iter()
+            .map(|(guid, link, date)| {
+                let date = date.map(|d| format!("<pubDate>{d}</pubDate>"));
+                format!(
+                    "<item><guid>{guid}</guid><title>{guid}</title><link>{link}</link>{}</item>",
+                    date.unwrap_or_default()
+                )
+            })
+            .collect();
+        let xml = format!(
+            r#"<?xml version="1.0"?><rss version="2.0"><channel><title>Test</title>{items}</channel></rss>"#
+        );
+        feed_rs::parser::parse(xml.as_bytes()).unwrap()
+    }
+
+    fn ids(feed: &Feed) -> Vec<(String, String)> {
+        feed.items
+            .iter()
+            .map(|i| (i.title.clone(), i.id.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn undated_items_keep_feed_order_and_ids_across_merges() {
+        let entries = [
+            ("c", "https://example.com/c", None),
+            (
+                "dated",
+                "https://example.com/d",
+                Some("Mon, 01 Jan 2024 00:00:00 GMT"),
+            ),
+            ("a", "https://example.com/a", None),
+            ("b", "https://example.com/b", None),
+        ];
+        let mut feed = Feed::default();
+        assert_eq!(feed.merge_items(rss(&entries), false), 4);
+        let first = ids(&feed);
+        let titles: Vec<&str> = first.iter().map(|(t, _)| t.as_str()).collect();
+        assert_eq!(titles, ["dated", "c", "a", "b"]);
+        assert!(feed.items[1..].iter().all(|i| i.timestamp == 0));
+
+        assert_eq!(feed.merge_items(rss(&entries), false), 0);
+        assert_eq!(ids(&feed), first);
+    }
+
+    fn titles(feed: &Feed) -> Vec<String> {
+        feed.items.iter().map(|i| i.title.clone()).collect()
+    }
+
+    #[test]
+    fn new_undated_items_take_their_place_in_the_feed() {
+        let undated = |names: &[String]| {
+            let links: Vec<String> = names
+                .iter()
+                .map(|n| format!("https://example.com/{n}"))
+                .collect();
+            let entries: Vec<(&str, &str, Option<&str>)> = names
+                .iter()
+                .zip(&links)
+                .map(|(n, l)| (n.as_str(), l.as_str(), None))
+                .collect();
+            rss(&entries)
+        };
+        let names = |list: &[&str]| list.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let mut feed = Feed::default();
+        feed.merge_items(undated(&names(&["a", "b"])), false);
+
+        assert_eq!(
+            feed.merge_items(undated(&names(&["new", "a", "b"])), false),
+            1
+        );
+        assert_eq!(titles(&feed), ["new", "a", "b"]);
+
+        // Enough new items at once to re-sort instead of inserting.
+        let mut many: Vec<String> = (0..=MERGE_INSERT_LIMIT).map(|n| format!("n{n}")).collect();
+        many.extend(names(&["new", "a", "b"]));
+        feed.merge_items(undated(&many), false);
+        assert_eq!(titles(&feed), many);
+    }
+
+    #[test]
+    fn colliding_ids_stay_distinct() {
+        // Two entries share a guid, and one of them also repeats its link.
+        let entries = [
+            ("dup", "https://example.com/a", None),
+            ("dup", "https://example.com/b", None),
+            ("dup", "https://example.com/a", None),
+        ];
+        let mut feed = Feed::default();
+        assert_eq!(feed.merge_items(rss(&entries), false), 3);
+        let first = ids(&feed);
+        let unique: HashSet<&str> = first.iter().map(|(_, id)| id.as_str()).collect();
+        assert_eq!(unique.len(), 3);
+        assert_eq!(
+            first[0].1,
+            Item::gen_id(Some("dup"), "https://example.com/a")
+        );
+        assert!(first.iter().all(|(_, id)| id.len() >= 40));
+
+        feed.merge_items(rss(&entries), false);
+        assert_eq!(ids(&feed), first);
+    }
+
+    #[test]
+    fn future_dates_are_capped_to_fetch_time() {
+        let entries = [
+            (
+                "future",
+                "https://example.com/f",
+                Some("Fri, 01 Jan 2100 00:00:00 GMT"),
+            ),
+            (
+                "past",
+                "https://example.com/p",
+                Some("Mon, 01 Jan 2024 00:00:00 GMT"),
+            ),
+        ];
+        let before = chrono::Utc::now().timestamp();
+        let mut feed = Feed::default();
+        feed.merge_items(rss(&entries), false);
+        let after = chrono::Utc::now().timestamp();
+        let future = &feed.items[0];
+        assert_eq!(future.title, "future");
+        assert!(future.future_dated);
+        assert!((before..=after).contains(&future.timestamp));
+        assert!(!feed.items[1].future_dated);
+
+        // Later fetches keep the time it was first seen.
+        feed.items[0].timestamp = before - 60;
+        feed.merge_items(rss(&entries), false);
+        assert_eq!(feed.items[0].timestamp, before - 60);
+        assert!(feed.items[0].future_dated);
+    }
+
+    #[test]
+    fn epoch_dates_sort_as_undated() {
+        let entries = [
+            (
+                "epoch",
+                "https://example.com/e",
+                Some("Thu, 01 Jan 1970 00:00:00 GMT"),
+            ),
+            ("undated", "https://example.com/u", None),
+            (
+                "dated",
+                "https://example.com/d",
+                Some("Mon, 01 Jan 2024 00:00:00 GMT"),
+            ),
+        ];
+        let mut feed = Feed::default();
+        feed.merge_items(rss(&entries), false);
+        let titles: Vec<&str> = feed.items.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, ["dated", "epoch", "undated"]);
+        assert_eq!(feed.items[1].timestamp, 0);
+        assert!(!feed.items[1].future_dated);
+        assert_eq!(feed.missing_dates, 2);
+    }
+
+    fn groups(names: &[&str]) -> Vec<Group> {
+        names
+            .iter()
+            .map(|name| Group {
+                name: name.to_string(),
+                ..Group::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn group_names_match_ignoring_case_and_whitespace() {
+        let groups = groups(&["News", "Tech"]);
+        assert_eq!(find_group(&groups, "news", None), Some(0));
+        assert_eq!(find_group(&groups, "  TECH ", None), Some(1));
+        assert_eq!(find_group(&groups, "Sport", None), None);
+    }
+
+    #[test]
+    fn find_group_skips_the_excluded_group() {
+        let groups = groups(&["News", "Tech"]);
+        // Renaming "News" to "news" is not a clash with itself.
+        assert_eq!(find_group(&groups, "news", Some(0)), None);
+        assert_eq!(find_group(&groups, "news", Some(1)), Some(0));
+    }
+
+    #[test]
+    fn find_or_create_group_reuses_existing_names() {
+        let mut groups = groups(&["News"]);
+        assert_eq!(find_or_create_group(&mut groups, " NEWS"), 0);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(find_or_create_group(&mut groups, "Tech"), 1);
+        assert_eq!(groups[1].name, "Tech");
+    }
+
+    /// An empty directory under the system temp dir, unique to this test.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mrss-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn compressed_database_round_trips() {
+        let json = temp_dir("db-gzip").join("db.json");
+        save_to(&json, &groups(&["News"]), true).unwrap();
+        assert!(!json.exists());
+        let head = fs::read(gz_path(&json)).unwrap();
+        assert!(head.starts_with(&GZIP_MAGIC));
+
+        let loaded = load_from(&json, true).unwrap();
+        assert_eq!(loaded[0].name, "News");
+    }
+
+    #[test]
+    fn saving_migrates_between_formats() {
+        let json = temp_dir("db-migrate").join("db.json");
+        save_to(&json, &groups(&["News"]), false).unwrap();
+        // Turning compression on still finds the plain file, and the next
+        // save replaces it.
+        let loaded = load_from(&json, true).unwrap();
+        assert_eq!(loaded[0].name, "News");
+        save_to(&json, &loaded, true).unwrap();
+        assert!(!json.exists() && gz_path(&json).exists());
+
+        save_to(&json, &load_from(&json, false).unwrap(), false).unwrap();
+        assert!(json.exists() && !gz_path(&json).exists());
+    }
+
+    #[test]
+    fn gzip_is_detected_by_content() {
+        let json = temp_dir("db-detect").join("db.json");
+        save_to(&json, &groups(&["News"]), true).unwrap();
+        fs::rename(gz_path(&json), &json).unwrap();
+        assert_eq!(load_from(&json, false).unwrap()[0].name, "News");
+        assert!(
+            load_from(&temp_dir("db-none").join("db.json"), true)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn changing_the_url_resets_health() {
+        let mut feed = Feed {
+            url: "https://old.example.com/feed".into(),
+            etag: Some("\"v1\"".into()),
+            error_streak: 3,
+            last_error: Some("404 Not Found".into()),
+            last_status: Some(404),
+            empty_fetch: true,
+            disabled: true,
+            ..Feed::default()
+        };
+        feed.merge_items(rss(&[("a", "https://example.com/a", None)]), false);
+        feed.set_url("https://new.example.com/feed".into());
+        assert_eq!(feed.url, "https://new.example.com/feed");
+        assert!(feed.etag.is_none() && feed.last_error.is_none() && feed.last_status.is_none());
+        assert_eq!(feed.error_streak, 0);
+        assert!(!feed.empty_fetch && !feed.disabled);
+        assert!(feed.is_due(0));
+        assert_eq!(feed.items.len(), 1);
     }
 }
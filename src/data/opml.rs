@@ -0,0 +1,154 @@
+//! OPML subscription import and export.
+
+use std::{fmt, sync::OnceLock};
+
+use regex::Regex;
+
+use super::{Feed, Group, find_or_create_group};
+use crate::net::{normalize_url, same_feed_url};
+
+/// Group used for feeds listed outside any category outline.
+const UNCATEGORIZED: &str = "Imported";
+
+/// A feed listed in an OPML document.
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    pub group: String,
+    pub url: String,
+    pub title: Option<String>,
+}
+
+/// What an import changed.
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub skipped: usize,
+    pub groups_created: usize,
+}
+
+impl fmt::Display for ImportSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "added {} feeds, skipped {} existing, created {} groups",
+            self.added, self.skipped, self.groups_created
+        )
+    }
+}
+
+/// List the feeds in an OPML document. Feeds take the name of the innermost
+/// enclosing outline as their group.
+pub fn parse_opml(text: &str) -> Vec<Subscription> {
+    static TAG: OnceLock<Regex> = OnceLock::new();
+    let tag = TAG.get_or_init(|| Regex::new(r"(?s)<outline\b([^>]*?)(/?)>|</outline\s*>").unwrap());
+    let mut stack: Vec<Option<String>> = Vec::new();
+    let mut subs = Vec::new();
+    for cap in tag.captures_iter(text) {
+        let Some(attrs) = cap.get(1) else {
+            stack.pop();
+            continue;
+        };
+        let attr = |name: &str| attribute(attrs.as_str(), name);
+        let self_closing = !cap[2].is_empty();
+        let title = attr("title").or_else(|| attr("text"));
+        match attr("xmlUrl") {
+            Some(url) => {
+                let group = stack
+                    .iter()
+                    .rev()
+                    .find_map(|g| g.clone())
+                    .unwrap_or_else(|| UNCATEGORIZED.into());
+                subs.push(Subscription { group, url, title });
+                if !self_closing {
+                    stack.push(None);
+                }
+            }
+            None if !self_closing => stack.push(title),
+            None => {}
+        }
+    }
+    subs
+}
+
+/// Add the feeds in `subs` that aren't subscribed to yet, in any group.
+/// Existing feeds are left untouched so their items, read state and titles
+/// survive re-importing an overlapping file.
+pub fn merge_subscriptions(groups: &mut Vec<Group>, subs: Vec<Subscription>) -> ImportSummary {
+    let mut summary = ImportSummary::default();
+    for sub in subs {
+        let url = normalize_url(&sub.url);
+        let exists = groups
+            .iter()
+            .flat_map(|g| &g.feeds)
+            .any(|f| same_feed_url(&f.url, &url));
+        if exists {
+            summary.skipped += 1;
+            continue;
+        }
+        let before = groups.len();
+        let g = find_or_create_group(groups, &sub.group);
+        summary.groups_created += groups.len() - before;
+        groups[g].feeds.push(Feed {
+            title: sub.title.unwrap_or_else(|| url.clone()),
+            url,
+            ..Feed::default()
+        });
+        summary.added += 1;
+    }
+    summary
+}
+
+/// Write `groups` as an OPML document with one outline per group.
+pub fn to_opml(groups: &[Group]) -> String {
+    let mut doc = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<opml version=\"2.0\">\n",
+        "  <head><title>Multi_RSS subscriptions</title></head>\n",
+        "  <body>\n",
+    ));
+    for group in groups {
+        let name = escape(&group.name);
+        doc.push_str(&format!("    <outline text=\"{0}\" title=\"{0}\">\n", name));
+        for feed in &group.feeds {
+            let title = escape(&feed.title);
+            doc.push_str(&format!(
+                "      <outline type=\"rss\" text=\"{0}\" title=\"{0}\" xmlUrl=\"{1}\"",
+                title,
+                escape(&feed.url)
+            ));
+            if let Some(home) = &feed.home_url {
+                doc.push_str(&format!(" htmlUrl=\"{}\"", escape(home)));
+            }
+            doc.push_str("/>\n");
+        }
+        doc.push_str("    </outline>\n");
+    }
+    doc.push_str("  </body>\n</opml>\n");
+    doc
+}
+
+/// Escape text for use in an XML attribute value.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Value of attribute `name` in an element's attribute list, unescaped.
+fn attribute(attrs: &str, name: &str) -> Option<String> {
+    static ATTR: OnceLock<Regex> = OnceLock::new();
+    let re = ATTR.get_or_init(|| Regex::new(r#"([\w:]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+    re.captures_iter(attrs)
+        .find(|c| c[1].eq_ignore_ascii_case(name))
+        .and_then(|c| c.get(2).or(c.get(3)))
+        .map(|v| {
+            v.as_str()
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&apos;", "'")
+                .replace("&amp;", "&")
+        })
+        .filter(|v| !v.trim().is_empty())
+}
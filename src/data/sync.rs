@@ -0,0 +1,220 @@
+//! Read-state sync through a shared file.
+//!
+//! Each machine merges its flags into the same small JSON file (kept in a
+//! synced folder such as Dropbox or Syncthing). For every item the most
+//! recently changed side wins, using [`Item::flags_updated`].
+
+use std::{collections::HashMap, fmt, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use super::{Group, Item};
+use crate::config;
+
+/// Flags of one item as stored in the sync file.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FlagState {
+    #[serde(default)]
+    pub read: bool,
+    #[serde(default)]
+    pub starred: bool,
+    #[serde(default)]
+    pub queued: bool,
+    /// When the flags last changed, as a Unix timestamp.
+    pub at: i64,
+    /// When the item was marked read, if it is read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_at: Option<i64>,
+}
+
+impl FlagState {
+    fn of(item: &Item) -> Self {
+        Self {
+            read: item.read,
+            starred: item.starred,
+            queued: item.queued,
+            at: item.flags_updated,
+            read_at: item.read_at,
+        }
+    }
+}
+
+/// What a sync changed.
+#[derive(Debug, Default)]
+pub struct SyncSummary {
+    /// Local items updated from the file.
+    pub pulled: usize,
+    /// File entries updated from local items.
+    pub pushed: usize,
+}
+
+impl fmt::Display for SyncSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "read state synced: {} updated here, {} shared",
+            self.pulled, self.pushed
+        )
+    }
+}
+
+/// Merge local flags with the sync file at `path`, creating it if missing,
+/// and write the result back. Entries for items this machine doesn't have
+/// are kept for the others. In read-only mode the file is only read.
+pub fn sync_read_state(
+    groups: &mut [Group],
+    path: &Path,
+) -> Result<SyncSummary, Box<dyn std::error::Error>> {
+    let mut shared: HashMap<String, FlagState> = match fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let mut summary = SyncSummary::default();
+    for group in groups.iter_mut() {
+        for item in group.feeds.iter_mut().flat_map(|f| &mut f.items) {
+            match shared.get(&item.id) {
+                Some(theirs) if theirs.at > item.flags_updated => {
+                    item.read = theirs.read;
+                    item.starred = theirs.starred;
+                    item.queued = theirs.queued;
+                    item.flags_updated = theirs.at;
+                    // Files from older versions don't say when; the change
+                    // time is the best guess.
+                    item.read_at = theirs.read.then(|| theirs.read_at.unwrap_or(theirs.at));
+                    summary.pulled += 1;
+                }
+                Some(theirs) if theirs.at == item.flags_updated => {}
+                // Items whose flags never changed aren't worth sharing.
+                None if item.flags_updated == 0 => {}
+                _ => {
+                    shared.insert(item.id.clone(), FlagState::of(item));
+                    summary.pushed += 1;
+                }
+            }
+        }
+        group.update_unread();
+    }
+    if !config::read_only() && (summary.pushed > 0 || !path.exists()) {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(&shared)?)?;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Feed;
+    use std::path::PathBuf;
+
+    /// A sync file path in an empty directory unique to this test.
+    fn sync_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mrss-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("read-state.json")
+    }
+
+    /// One group holding items `(id, read, flags_updated)`.
+    fn groups(items: &[(&str, bool, i64)]) -> Vec<Group> {
+        let items = items
+            .iter()
+            .map(|&(id, read, at)| {
+                let mut item: Item = serde_json::from_value(serde_json::json!({
+                    "id": id,
+                    "title": id,
+                    "link": format!("https://example.com/{id}"),
+                    "timestamp": 1_700_000_000,
+                }))
+                .unwrap();
+                item.read = read;
+                item.flags_updated = at;
+                item
+            })
+            .collect();
+        vec![Group {
+            name: "News".into(),
+            feeds: vec![Feed {
+                items,
+                ..Feed::default()
+            }],
+            ..Group::default()
+        }]
+    }
+
+    fn write_shared(path: &Path, entries: &[(&str, bool, i64)]) {
+        let shared: HashMap<&str, FlagState> = entries
+            .iter()
+            .map(|&(id, read, at)| {
+                let state = FlagState {
+                    read,
+                    starred: false,
+                    queued: false,
+                    at,
+                    read_at: None,
+                };
+                (id, state)
+            })
+            .collect();
+        fs::write(path, serde_json::to_string(&shared).unwrap()).unwrap();
+    }
+
+    fn read_shared(path: &Path) -> HashMap<String, FlagState> {
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn newer_shared_flags_win() {
+        let path = sync_file("sync-pull");
+        write_shared(&path, &[("a", true, 200)]);
+        let mut groups = groups(&[("a", false, 100)]);
+
+        let summary = sync_read_state(&mut groups, &path).unwrap();
+        assert_eq!((summary.pulled, summary.pushed), (1, 0));
+        let item = &groups[0].feeds[0].items[0];
+        assert!(item.read);
+        assert_eq!((item.flags_updated, item.read_at), (200, Some(200)));
+        assert_eq!(groups[0].unread_count, 0);
+    }
+
+    #[test]
+    fn newer_local_flags_are_shared() {
+        let path = sync_file("sync-push");
+        write_shared(&path, &[("a", true, 100), ("elsewhere", true, 50)]);
+        let mut groups = groups(&[("a", false, 200)]);
+
+        let summary = sync_read_state(&mut groups, &path).unwrap();
+        assert_eq!((summary.pulled, summary.pushed), (0, 1));
+        assert!(!groups[0].feeds[0].items[0].read);
+        let shared = read_shared(&path);
+        assert!(!shared["a"].read);
+        assert_eq!(shared["a"].at, 200);
+        assert!(shared.contains_key("elsewhere"));
+    }
+
+    #[test]
+    fn missing_file_is_created_with_changed_items() {
+        let path = sync_file("sync-missing");
+        let mut groups = groups(&[("a", true, 100), ("untouched", false, 0)]);
+
+        let summary = sync_read_state(&mut groups, &path).unwrap();
+        assert_eq!((summary.pulled, summary.pushed), (0, 1));
+        let shared = read_shared(&path);
+        assert_eq!(shared.len(), 1);
+        assert!(shared["a"].read);
+    }
+
+    #[test]
+    fn corrupt_file_is_reported_and_left_alone() {
+        let path = sync_file("sync-corrupt");
+        fs::write(&path, "{not json").unwrap();
+        let mut groups = groups(&[("a", true, 100)]);
+
+        assert!(sync_read_state(&mut groups, &path).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{not json");
+        assert!(groups[0].feeds[0].items[0].read);
+    }
+}
@@ -3,53 +3,350 @@ mod data;
 mod net;
 mod tui;
 
-use crate::config::Config;
+use crate::config::{Config, Dirs};
+use crate::net::refresh::{FetchTarget, RefreshStatus};
 use chrono::Utc;
 use std::{
+    io::IsTerminal,
+    path::PathBuf,
     sync::{Arc, Mutex, mpsc},
     thread,
     time::Duration,
 };
 
+/// Environment variable that, when non-empty, acts like `--no-altscreen`.
+const NO_ALT_SCREEN_ENV: &str = "MULTI_RSS_NO_ALTSCREEN";
+
+/// One-shot command run instead of the terminal UI.
+enum Command {
+    /// `export-json <path>`: write a JSON backup of the database.
+    ExportJson(PathBuf),
+    /// `import-json <path>`: merge a JSON backup into the database.
+    ImportJson(PathBuf),
+    /// `add <url> [--group <name>]`: subscribe to a feed.
+    Add { url: String, group: Option<String> },
+}
+
+/// Command-line options.
+struct Args {
+    dirs: Dirs,
+    /// `--no-color`: draw without colors and with ASCII markers.
+    no_color: bool,
+    /// `--no-altscreen`: draw on the normal screen, e.g. to keep panics
+    /// visible.
+    no_alt_screen: bool,
+    /// `--status-file <path>`: JSON summary rewritten after each refresh.
+    status_file: Option<PathBuf>,
+    command: Option<Command>,
+}
+
+/// Read `--profile`, `--config-dir`, `--data-dir`, `--status-file` and
+/// `--group` (as `--flag value` or `--flag=value`), the directories falling
+/// back to their environment variables, `--no-color`, `--no-altscreen` and an
+/// optional command.
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut dirs = Dirs::default();
+    let mut no_color = false;
+    let mut no_alt_screen = false;
+    let mut status_file = None;
+    let mut group = None;
+    let mut command = None;
+    while let Some(arg) = args.next() {
+        if arg == "--no-color" {
+            no_color = true;
+            continue;
+        }
+        if arg == "--no-altscreen" {
+            no_alt_screen = true;
+            continue;
+        }
+        if matches!(arg.as_str(), "export-json" | "import-json") {
+            let path = PathBuf::from(args.next().ok_or_else(|| format!("{} needs a path", arg))?);
+            command = Some(if arg == "export-json" {
+                Command::ExportJson(path)
+            } else {
+                Command::ImportJson(path)
+            });
+            continue;
+        }
+        if arg == "add" {
+            let url = args.next().ok_or("add needs a feed URL")?;
+            command = Some(Command::Add { url, group: None });
+            continue;
+        }
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
+        if !matches!(
+            flag.as_str(),
+            "--profile" | "--config-dir" | "--data-dir" | "--status-file" | "--group"
+        ) {
+            return Err(format!("unknown argument '{}'", flag));
+        }
+        let value = match inline {
+            Some(value) => value,
+            None => args
+                .next()
+                .ok_or_else(|| format!("{} needs a value", flag))?,
+        };
+        match flag.as_str() {
+            "--profile" => dirs.profile = Some(value),
+            "--config-dir" => dirs.config = Some(value.into()),
+            "--data-dir" => dirs.data = Some(value.into()),
+            "--group" => group = Some(value),
+            _ => status_file = Some(value.into()),
+        }
+    }
+    match &mut command {
+        Some(Command::Add { group: target, .. }) => *target = group,
+        _ if group.is_some() => return Err("--group only applies to add".into()),
+        _ => {}
+    }
+    let env = |name| std::env::var(name).ok().filter(|v: &String| !v.is_empty());
+    dirs.profile = dirs.profile.or_else(|| env(config::PROFILE_ENV));
+    dirs.config = dirs
+        .config
+        .or_else(|| env(config::CONFIG_DIR_ENV).map(Into::into));
+    dirs.data = dirs
+        .data
+        .or_else(|| env(config::DATA_DIR_ENV).map(Into::into));
+    Ok(Args {
+        dirs,
+        no_color,
+        no_alt_screen: no_alt_screen || env(NO_ALT_SCREEN_ENV).is_some(),
+        status_file,
+        command,
+    })
+}
+
+/// Make sure the config and data directories can be written before the UI
+/// starts, so a read-only filesystem doesn't surface as an error at quit.
+/// For each one that can't, ask whether to use a directory under the system
+/// temp dir instead, run without saving, or quit. Without a terminal to ask
+/// on, runs without saving.
+fn check_dirs(dirs: &mut Dirs) -> Result<(), String> {
+    let checks = [
+        ("config", dirs.config_dir(), config::CONFIG_DIR_ENV),
+        ("data", dirs.data_dir(), config::DATA_DIR_ENV),
+    ];
+    for (kind, dir, env) in checks {
+        let Some(dir) = dir else {
+            continue;
+        };
+        let Err(e) = config::check_writable(&dir) else {
+            continue;
+        };
+        eprintln!(
+            "warning: the {} directory {} is not writable: {}",
+            kind,
+            dir.display(),
+            e
+        );
+        eprintln!("(use --{}-dir or {} to choose another one)", kind, env);
+        let fallback = std::env::temp_dir().join("multi-rss").join(kind);
+        if !std::io::stdin().is_terminal() {
+            eprintln!("running read-only: changes will not be saved");
+            dirs.read_only = true;
+            return Ok(());
+        }
+        eprint!(
+            "[t] use {} instead, [r] run read-only, [q] quit: ",
+            fallback.display()
+        );
+        let mut answer = String::new();
+        std::io::stdin()
+            .read_line(&mut answer)
+            .map_err(|e| e.to_string())?;
+        match answer.trim() {
+            "t" | "T" => {
+                if kind == "config" {
+                    dirs.config = Some(fallback);
+                } else {
+                    dirs.data = Some(fallback);
+                }
+            }
+            "r" | "R" => {
+                dirs.read_only = true;
+                return Ok(());
+            }
+            _ => {
+                return Err(format!(
+                    "{} directory {} is not writable",
+                    kind,
+                    dir.display()
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_command(command: Command) -> Result<(), Box<dyn std::error::Error>> {
+    let config = Config::load()?;
+    data::set_compress_db(config.storage.compress_db);
+    let mut groups = data::load_db()?;
+    match command {
+        Command::ExportJson(path) => {
+            data::backup::export_json(&groups, &path)?;
+            println!("exported to {}", path.display());
+        }
+        Command::ImportJson(path) => {
+            let summary = data::backup::import_json(&mut groups, &path)?;
+            data::save_db(&groups)?;
+            println!("{}", summary);
+        }
+        Command::Add { url, group } => {
+            let url = net::normalize_url(&url);
+            if !url::Url::parse(&url).is_ok_and(|u| u.has_host()) {
+                return Err(format!("invalid URL: {}", url).into());
+            }
+            if let Some(other) = groups.iter().find(|g| {
+                g.feeds
+                    .iter()
+                    .any(|feed| net::same_feed_url(&feed.url, &url))
+            }) {
+                return Err(format!("already subscribed in '{}'", other.name).into());
+            }
+            let name = group
+                .or(config.ui.default_group)
+                .unwrap_or_else(|| "Feeds".into());
+            let target = data::find_or_create_group(&mut groups, &name);
+            groups[target].feeds.push(data::Feed {
+                url: url.clone(),
+                title: url.clone(),
+                ..data::Feed::default()
+            });
+            data::save_db(&groups)?;
+            println!("added {} to '{}'", url, groups[target].name);
+        }
+    }
+    Ok(())
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = parse_args()?;
+    match config::migrate_legacy_dirs() {
+        Ok(moved) => {
+            for dir in moved {
+                eprintln!("moved data from earlier versions to {}", dir.display());
+            }
+        }
+        Err(e) => eprintln!("warning: could not move the old rssq folder: {}", e),
+    }
+    if args.command.is_none() {
+        check_dirs(&mut args.dirs)?;
+    }
+    let read_only = args.dirs.read_only;
+    config::set_dirs(args.dirs)?;
+    if let Some(command) = args.command {
+        return run_command(command);
+    }
     let config = Config::load()?;
-    let groups = Arc::new(Mutex::new(data::load_db().unwrap_or_default()));
+    data::set_compress_db(config.storage.compress_db);
+    // A database that exists but can't be read is reported rather than
+    // replaced by an empty one on the next save.
+    let mut db = match data::load_db() {
+        Err(data::StoreError::NoDataDir) => Vec::new(),
+        result => result.map_err(|e| e.to_string())?,
+    };
+    let sync_message = config.sync.read_state_file.as_ref().map(|path| {
+        match data::sync::sync_read_state(&mut db, path) {
+            Ok(summary) => summary.to_string(),
+            Err(e) => format!("read state sync failed: {}", e),
+        }
+    });
+    let schedule_message = config
+        .schedule
+        .queue_open_at
+        .as_ref()
+        .filter(|_| config.schedule.queue_open_time().is_none())
+        .map(|at| format!("ignoring schedule.queue_open_at '{}': expected HH:MM", at));
+    let groups = Arc::new(Mutex::new(db));
     let (tx, rx) = mpsc::channel();
     let interval = config.refresh.interval_secs;
+    let max_failures = config.refresh.max_failures;
+    let lenient = config.refresh.lenient_parsing;
+    let skip_linkless = config.refresh.skip_linkless_entries;
+    let limits = config.net.clone();
     let groups_clone = Arc::clone(&groups);
     thread::spawn(move || {
         let rt = tokio::runtime::Runtime::new().unwrap();
         loop {
+            let _ = tx.send(RefreshStatus::Started);
             let mut new_items = 0;
-            rt.block_on(async {
-                let mut guard = groups_clone.lock().unwrap();
-                for group in guard.iter_mut() {
-                    for feed in group.feeds.iter_mut() {
-                        let prev = feed.items.len();
-                        if let Ok((etag, last, Some(parsed))) = net::fetch_feed(
-                            &feed.url,
-                            feed.etag.as_deref(),
-                            feed.last_modified.as_deref(),
-                        )
-                        .await
-                        {
-                            feed.etag = etag;
-                            feed.last_modified = last;
-                            feed.merge_items(parsed);
-                            if feed.items.len() > prev {
-                                new_items += feed.items.len() - prev;
-                            }
-                        }
+            // Snapshot the feeds so the lock isn't held while fetching.
+            let now = Utc::now().timestamp();
+            let targets: Vec<FetchTarget> = {
+                let guard = groups_clone.lock().unwrap();
+                guard
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(g, group)| {
+                        group
+                            .feeds
+                            .iter()
+                            .filter(|feed| feed.is_due(now))
+                            .map(move |feed| FetchTarget {
+                                group: g,
+                                url: feed.url.clone(),
+                                etag: feed.etag.clone(),
+                                last_modified: feed.last_modified.clone(),
+                            })
+                    })
+                    .collect()
+            };
+            rt.block_on(net::refresh::fetch_all(
+                targets,
+                &limits,
+                lenient,
+                |target: FetchTarget, result| {
+                    if let Err(e) = &result {
+                        let _ = tx.send(RefreshStatus::Failed {
+                            url: target.url.clone(),
+                            error: e.to_string(),
+                        });
                     }
+                    let mut guard = groups_clone.lock().unwrap();
+                    let Some(group) = guard.get_mut(target.group) else {
+                        return;
+                    };
+                    let Some(feed) = group.feeds.iter_mut().find(|feed| feed.url == target.url)
+                    else {
+                        return;
+                    };
+                    new_items += net::refresh::apply_fetch(
+                        feed,
+                        result,
+                        Utc::now().timestamp(),
+                        max_failures,
+                        skip_linkless,
+                    );
                     group.update_unread();
-                }
+                },
+            ));
+            let _ = tx.send(RefreshStatus::Finished {
+                at: Utc::now(),
+                new_items,
             });
-            let _ = tx.send((Utc::now(), new_items));
             thread::sleep(Duration::from_secs(interval));
         }
     });
 
     let mut app = tui::AppState::new(config, groups, rx);
+    let read_only_message = read_only.then(|| "read-only: changes will not be saved".to_string());
+    let notices: Vec<String> = [read_only_message, sync_message, schedule_message]
+        .into_iter()
+        .flatten()
+        .collect();
+    app.message = (!notices.is_empty()).then(|| notices.join("; "));
+    app.alt_screen = !args.no_alt_screen;
+    app.status_file = args.status_file;
+    // https://no-color.org: any non-empty value disables colors.
+    app.plain = args.no_color
+        || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+        || !std::io::stdout().is_terminal();
     tui::run_app(&mut app)?;
     Ok(())
 }
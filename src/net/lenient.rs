@@ -0,0 +1,149 @@
+//! Best-effort repair of malformed feed documents.
+//!
+//! Used when strict parsing fails. The repairs are deliberately conservative:
+//! they only touch constructs that make an XML parser bail out and leave
+//! everything else byte-for-byte intact.
+
+/// HTML entities commonly found in feeds that XML does not predeclare, with
+/// their code points.
+const HTML_ENTITIES: &[(&str, u32)] = &[
+    ("nbsp", 160),
+    ("iexcl", 161),
+    ("cent", 162),
+    ("pound", 163),
+    ("yen", 165),
+    ("sect", 167),
+    ("copy", 169),
+    ("laquo", 171),
+    ("reg", 174),
+    ("deg", 176),
+    ("plusmn", 177),
+    ("middot", 183),
+    ("raquo", 187),
+    ("frac12", 189),
+    ("iquest", 191),
+    ("agrave", 224),
+    ("aacute", 225),
+    ("auml", 228),
+    ("ccedil", 231),
+    ("egrave", 232),
+    ("eacute", 233),
+    ("ouml", 246),
+    ("times", 215),
+    ("uuml", 252),
+    ("szlig", 223),
+    ("ndash", 8211),
+    ("mdash", 8212),
+    ("lsquo", 8216),
+    ("rsquo", 8217),
+    ("sbquo", 8218),
+    ("ldquo", 8220),
+    ("rdquo", 8221),
+    ("bdquo", 8222),
+    ("dagger", 8224),
+    ("bull", 8226),
+    ("hellip", 8230),
+    ("prime", 8242),
+    ("euro", 8364),
+    ("trade", 8482),
+    ("larr", 8592),
+    ("rarr", 8594),
+];
+
+/// Entities every XML parser understands.
+const XML_ENTITIES: &[&str] = &["amp", "lt", "gt", "quot", "apos"];
+
+/// Repair common problems in a feed document:
+///
+/// - invalid UTF-8 sequences are replaced,
+/// - anything before the first `<` (BOMs, stray whitespace) is dropped,
+/// - control characters XML forbids are removed,
+/// - HTML entities are rewritten as numeric references and bare `&` escaped.
+///
+/// CDATA sections are copied unchanged.
+pub fn sanitize(bytes: &[u8]) -> Vec<u8> {
+    let text = String::from_utf8_lossy(bytes);
+    let text = text.find('<').map_or(&text[..], |start| &text[start..]);
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while !rest.is_empty() {
+        if rest.starts_with("<![CDATA[") {
+            let end = rest.find("]]>").map_or(rest.len(), |e| e + 3);
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+        } else if let Some(after) = rest.strip_prefix('&') {
+            let (replacement, consumed) = fix_entity(after);
+            out.push_str(&replacement);
+            rest = &after[consumed..];
+        } else {
+            let c = rest.chars().next().unwrap_or_default();
+            if !c.is_control() || matches!(c, '\t' | '\n' | '\r') {
+                out.push(c);
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    out.into_bytes()
+}
+
+/// Replacement for a `&` followed by `after`, and how many bytes of `after`
+/// it covers: the original entity if XML understands it, a numeric reference
+/// for known HTML entities, or an escaped ampersand otherwise.
+fn fix_entity(after: &str) -> (String, usize) {
+    let Some(end) = after.find(';').filter(|&e| e <= 32) else {
+        return ("&amp;".into(), 0);
+    };
+    let name = &after[..end];
+    let numeric = name.strip_prefix('#').is_some_and(|n| {
+        n.strip_prefix(['x', 'X']).map_or(
+            !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()),
+            |h| !h.is_empty() && h.bytes().all(|b| b.is_ascii_hexdigit()),
+        )
+    });
+    if numeric || XML_ENTITIES.contains(&name) {
+        return ("&".into(), 0);
+    }
+    match HTML_ENTITIES.iter().find(|(n, _)| *n == name) {
+        Some((_, code)) => (format!("&#{};", code), end + 1),
+        None => ("&amp;".into(), 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A BOM, bare `&`s, an HTML entity and a control character.
+    const MALFORMED: &[u8] = b"\xef\xbb\xbf\n<?xml version=\"1.0\"?>\
+<rss version=\"2.0\"><channel><title>Tom & Jerry</title>\
+<item><title>Caf&eacute; news\x0b</title><link>https://example.com/?a=1&b=2</link>\
+<description><![CDATA[<p>Fish & chips</p>]]></description></item>\
+</channel></rss>";
+
+    #[test]
+    fn sanitized_sample_parses() {
+        let fixed = sanitize(MALFORMED);
+        let feed = feed_rs::parser::parse(&fixed[..]).unwrap();
+        assert_eq!(feed.title.unwrap().content, "Tom & Jerry");
+        let entry = &feed.entries[0];
+        assert_eq!(entry.title.as_ref().unwrap().content, "Café news");
+        assert_eq!(entry.links[0].href, "https://example.com/?a=1&b=2");
+        assert!(
+            entry
+                .summary
+                .as_ref()
+                .unwrap()
+                .content
+                .contains("Fish & chips")
+        );
+    }
+
+    #[test]
+    fn valid_entities_are_left_alone() {
+        let text = b"<a>&amp; &lt; &#233; &#xE9; &foo</a>";
+        assert_eq!(
+            String::from_utf8(sanitize(text)).unwrap(),
+            "<a>&amp; &lt; &#233; &#xE9; &amp;foo</a>"
+        );
+    }
+}
@@ -2,23 +2,140 @@
 
 //! Networking and feed fetching utilities.
 
+use std::{borrow::Cow, sync::OnceLock};
+
+use chrono::{DateTime, Duration, Utc};
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
 use feed_rs::parser;
+use regex::bytes::Regex;
 use reqwest::{Client, StatusCode, header};
+use url::Url;
+
+/// Outcome of a successful feed request.
+#[derive(Debug)]
+pub struct FetchOutcome {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// Parsed feed, or `None` when the server answered `304 Not Modified`.
+    pub feed: Option<feed_rs::model::Feed>,
+    /// Earliest time the server would like to be polled again, derived from
+    /// `Cache-Control: max-age` or `Expires`.
+    pub next_fetch: Option<DateTime<Utc>>,
+    /// HTTP status code of the response.
+    pub status: u16,
+    /// Whether the document only parsed after [`lenient::sanitize`].
+    pub lenient: bool,
+    /// Polling interval in seconds suggested by the document itself; see
+    /// [`update_interval`].
+    pub update_interval: Option<i64>,
+}
+
+/// Longest polling interval taken from a feed's own hints, so a feed that
+/// claims to update yearly is still checked daily.
+const MAX_UPDATE_INTERVAL_SECS: i64 = 24 * 60 * 60;
+
+/// Cooldown applied when a server rate-limits us without a `Retry-After`.
+const DEFAULT_RETRY_AFTER_SECS: i64 = 30 * 60;
+
+/// The server asked us to back off (`429`, or `503` with `Retry-After`).
+#[derive(Debug)]
+pub struct RateLimited {
+    pub until: DateTime<Utc>,
+    pub status: u16,
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "rate-limited until {}", self.until.format("%H:%M"))
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// Why a feed or article couldn't be fetched. Displays as a short message
+/// naming the kind of failure, for the feed health view and status bar.
+#[derive(Debug)]
+pub enum FetchError {
+    /// The request didn't complete: DNS, connection, TLS or timeout.
+    Request(reqwest::Error),
+    /// The server asked us to back off.
+    RateLimited(RateLimited),
+    /// The server answered with an unsuccessful HTTP status.
+    Status(u16),
+    /// The document isn't a feed we can parse.
+    Parse(parser::ParseFeedError),
+}
+
+impl FetchError {
+    /// HTTP status code of the response, if the server responded.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::Request(e) => e.status().map(|s| s.as_u16()),
+            Self::RateLimited(limit) => Some(limit.status),
+            Self::Status(status) => Some(*status),
+            Self::Parse(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for FetchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Request(e) if e.is_timeout() => write!(f, "timed out"),
+            Self::Request(e) if e.is_connect() => write!(f, "could not connect: {}", e),
+            Self::Request(e) => write!(f, "request failed: {}", e),
+            Self::RateLimited(limit) => limit.fmt(f),
+            Self::Status(status) => {
+                let kind = match status {
+                    401 | 403 => "access denied",
+                    404 => "not found",
+                    410 => "gone",
+                    500..=599 => "server error",
+                    _ => "unexpected response",
+                };
+                write!(f, "{} (HTTP {})", kind, status)
+            }
+            Self::Parse(e) => write!(f, "not a valid feed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Request(e) => Some(e),
+            Self::RateLimited(limit) => Some(limit),
+            Self::Status(_) => None,
+            Self::Parse(e) => Some(e),
+        }
+    }
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Request(e)
+    }
+}
+
+impl From<parser::ParseFeedError> for FetchError {
+    fn from(e: parser::ParseFeedError) -> Self {
+        Self::Parse(e)
+    }
+}
 
 /// Fetch a feed from the network respecting HTTP caching headers.
 ///
 /// `etag` and `last_modified` are previously cached header values. If the
-/// remote server returns `304 Not Modified`, `None` will be returned for the
-/// feed data. The returned tuple contains the new header values along with the
-/// optional parsed feed.
+/// remote server returns `304 Not Modified`, the returned feed is `None` and
+/// the cached header values are carried over when the server omits them.
+/// Rate-limit responses are reported as [`FetchError::RateLimited`]. With
+/// `lenient` set, documents the parser rejects are repaired and parsed again.
 pub async fn fetch_feed(
     url: &str,
     etag: Option<&str>,
     last_modified: Option<&str>,
-) -> Result<
-    (Option<String>, Option<String>, Option<feed_rs::model::Feed>),
-    Box<dyn std::error::Error>,
-> {
+    lenient: bool,
+) -> Result<FetchOutcome, FetchError> {
     let client = Client::builder().build()?;
     let mut req = client.get(url);
     if let Some(et) = etag {
@@ -30,6 +147,33 @@ pub async fn fetch_feed(
 
     let resp = req.send().await?;
 
+    let retry_after = resp
+        .headers()
+        .get(header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| parse_retry_after(v, Utc::now()));
+    let status = resp.status();
+    match (status, retry_after) {
+        (StatusCode::TOO_MANY_REQUESTS, until) => {
+            let until =
+                until.unwrap_or_else(|| Utc::now() + Duration::seconds(DEFAULT_RETRY_AFTER_SECS));
+            return Err(FetchError::RateLimited(RateLimited {
+                until,
+                status: status.as_u16(),
+            }));
+        }
+        (StatusCode::SERVICE_UNAVAILABLE, Some(until)) => {
+            return Err(FetchError::RateLimited(RateLimited {
+                until,
+                status: status.as_u16(),
+            }));
+        }
+        _ => {}
+    }
+    if !status.is_success() && status != StatusCode::NOT_MODIFIED {
+        return Err(FetchError::Status(status.as_u16()));
+    }
+
     let new_etag = resp
         .headers()
         .get(header::ETAG)
@@ -40,18 +184,413 @@ pub async fn fetch_feed(
         .get(header::LAST_MODIFIED)
         .and_then(|v| v.to_str().ok())
         .map(|s| s.to_string());
+    let next_fetch = cache_expiry(resp.headers(), Utc::now());
 
-    if resp.status() == StatusCode::NOT_MODIFIED {
-        return Ok((
-            new_etag.or(etag.map(|s| s.to_string())),
-            new_last.or(last_modified.map(|s| s.to_string())),
-            None,
-        ));
+    if status == StatusCode::NOT_MODIFIED {
+        return Ok(FetchOutcome {
+            etag: new_etag.or(etag.map(|s| s.to_string())),
+            last_modified: new_last.or(last_modified.map(|s| s.to_string())),
+            feed: None,
+            next_fetch,
+            status: status.as_u16(),
+            lenient: false,
+            update_interval: None,
+        });
     }
 
+    let content_type = resp
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
     let bytes = resp.bytes().await?;
-    let feed = parser::parse(&bytes[..])?;
-    Ok((new_etag, new_last, Some(feed)))
+    let bytes = to_utf8(&bytes, content_type.as_deref());
+    let (feed, repaired) = match parser::parse(&bytes[..]) {
+        Ok(feed) => (feed, false),
+        Err(e) if !lenient => return Err(e.into()),
+        Err(e) => match parser::parse(&lenient::sanitize(&bytes)[..]) {
+            Ok(feed) => (feed, true),
+            // Report the original error; it describes the real document.
+            Err(_) => return Err(e.into()),
+        },
+    };
+    let update_interval = update_interval(&feed, &bytes);
+    Ok(FetchOutcome {
+        etag: new_etag,
+        last_modified: new_last,
+        feed: Some(feed),
+        next_fetch,
+        status: status.as_u16(),
+        lenient: repaired,
+        update_interval,
+    })
+}
+
+/// Polling interval in seconds a feed asks for: RSS `<ttl>` (minutes), or
+/// else the syndication module's `sy:updatePeriod` divided by
+/// `sy:updateFrequency`. Capped at [`MAX_UPDATE_INTERVAL_SECS`].
+fn update_interval(feed: &feed_rs::model::Feed, raw: &[u8]) -> Option<i64> {
+    static PERIOD: OnceLock<Regex> = OnceLock::new();
+    static FREQUENCY: OnceLock<Regex> = OnceLock::new();
+    let secs = match feed.ttl {
+        Some(minutes) => i64::from(minutes) * 60,
+        None => {
+            let period = PERIOD.get_or_init(|| {
+                Regex::new(r"(?i)<sy:updatePeriod>\s*(hourly|daily|weekly|monthly|yearly)\s*<")
+                    .unwrap()
+            });
+            let frequency = FREQUENCY
+                .get_or_init(|| Regex::new(r"(?i)<sy:updateFrequency>\s*(\d+)\s*<").unwrap());
+            let period = match period
+                .captures(raw)?
+                .get(1)?
+                .as_bytes()
+                .to_ascii_lowercase()
+                .as_slice()
+            {
+                b"hourly" => 60 * 60,
+                b"daily" => 24 * 60 * 60,
+                b"weekly" => 7 * 24 * 60 * 60,
+                b"monthly" => 30 * 24 * 60 * 60,
+                _ => 365 * 24 * 60 * 60,
+            };
+            let times = frequency
+                .captures(raw)
+                .and_then(|c| std::str::from_utf8(&c[1]).ok()?.parse::<i64>().ok())
+                .filter(|&n| n > 0)
+                .unwrap_or(1);
+            period / times
+        }
+    };
+    (secs > 0).then_some(secs.min(MAX_UPDATE_INTERVAL_SECS))
 }
 
+/// Fetch a web page for the built-in reader, decoded to UTF-8.
+pub async fn fetch_article(url: &str) -> Result<String, FetchError> {
+    let resp = Client::builder().build()?.get(url).send().await?;
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(FetchError::Status(status.as_u16()));
+    }
+    let content_type = resp
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+    let bytes = resp.bytes().await?;
+    Ok(String::from_utf8_lossy(&to_utf8(&bytes, content_type.as_deref())).into_owned())
+}
+
+/// Transcode a feed document to UTF-8 so titles from feeds in legacy
+/// charsets aren't garbled.
+///
+/// The charset is taken from the `Content-Type` header, then the XML
+/// declaration. Undeclared documents that aren't valid UTF-8 are assumed to
+/// be Windows-1252. When transcoding, the declaration is rewritten to say
+/// UTF-8 so the parser doesn't decode the text a second time.
+fn to_utf8<'a>(bytes: &'a [u8], content_type: Option<&str>) -> Cow<'a, [u8]> {
+    let declared = content_type
+        .and_then(header_charset)
+        .or_else(|| xml_encoding(bytes))
+        .and_then(|label| Encoding::for_label(label.trim().as_bytes()));
+    let encoding = match declared {
+        Some(encoding) => encoding,
+        None if std::str::from_utf8(bytes).is_ok() => return Cow::Borrowed(bytes),
+        None => WINDOWS_1252,
+    };
+    if encoding == UTF_8 {
+        return Cow::Borrowed(bytes);
+    }
+    let (text, _, _) = encoding.decode(bytes);
+    let text = match xml_declaration(&text) {
+        Some(decl) => format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>{}",
+            &text[decl..]
+        ),
+        None => text.into_owned(),
+    };
+    Cow::Owned(text.into_bytes())
+}
+
+/// The `charset` parameter of a `Content-Type` header value.
+fn header_charset(content_type: &str) -> Option<&str> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"'))
+    })
+}
+
+/// The `encoding` attribute of the XML declaration, if any.
+fn xml_encoding(bytes: &[u8]) -> Option<&str> {
+    let head = &bytes[..bytes.len().min(256)];
+    let head = std::str::from_utf8(head)
+        .unwrap_or_else(|e| std::str::from_utf8(&head[..e.valid_up_to()]).unwrap_or_default());
+    let decl = &head[..xml_declaration(head)?];
+    let value = &decl[decl.find("encoding")? + "encoding".len()..];
+    let value = value.trim_start().strip_prefix('=')?.trim_start();
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let value = &value[1..];
+    value.find(quote).map(|end| &value[..end])
+}
+
+/// Byte offset just past the XML declaration at the start of `text`.
+fn xml_declaration(text: &str) -> Option<usize> {
+    let start = text.find("<?xml")?;
+    if !text[..start]
+        .trim_start_matches('\u{feff}')
+        .trim()
+        .is_empty()
+    {
+        return None;
+    }
+    text[start..].find("?>").map(|end| start + end + 2)
+}
+
+/// Canonical form of a feed URL, used to spot duplicate subscriptions.
+///
+/// The host is lowercased, default ports and fragments are dropped, and a
+/// trailing slash is removed from non-root paths. A missing scheme is taken
+/// to be `https`. Strings that don't parse as URLs are returned trimmed.
+pub fn normalize_url(url: &str) -> String {
+    let url = url.trim();
+    let parse = |s: &str| Url::parse(s).ok().filter(|u| u.has_host());
+    let Some(mut parsed) = parse(url).or_else(|| parse(&format!("https://{}", url))) else {
+        return url.to_string();
+    };
+    parsed.set_fragment(None);
+    let path = parsed.path().to_string();
+    if path.len() > 1 && path.ends_with('/') {
+        parsed.set_path(path.trim_end_matches('/'));
+    }
+    parsed.to_string()
+}
+
+/// Whether two feed URLs point at the same feed, ignoring `http` vs `https`.
+pub fn same_feed_url(a: &str, b: &str) -> bool {
+    let strip = |u: &str| {
+        let u = normalize_url(u);
+        match u.split_once("://") {
+            Some((scheme, rest)) if scheme == "http" || scheme == "https" => rest.to_string(),
+            _ => u,
+        }
+    };
+    strip(a) == strip(b)
+}
+
+/// Parse a `Retry-After` value, either delay seconds or an HTTP date.
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<i64>() {
+        return Some(now + Duration::seconds(secs.max(0)));
+    }
+    DateTime::parse_from_rfc2822(value)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Work out when a response stops being fresh.
+///
+/// `Cache-Control: max-age` takes precedence over `Expires`, as in HTTP
+/// caching. `no-cache`/`no-store` yield `None`.
+fn cache_expiry(headers: &header::HeaderMap, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if let Some(cc) = headers
+        .get(header::CACHE_CONTROL)
+        .and_then(|v| v.to_str().ok())
+    {
+        let mut max_age = None;
+        for directive in cc.split(',').map(str::trim) {
+            let lower = directive.to_ascii_lowercase();
+            if lower == "no-cache" || lower == "no-store" {
+                return None;
+            }
+            if let Some(secs) = lower.strip_prefix("max-age=") {
+                max_age = secs.trim_matches('"').parse::<i64>().ok();
+            }
+        }
+        if let Some(secs) = max_age {
+            return Some(now + Duration::seconds(secs.max(0)));
+        }
+    }
+    headers
+        .get(header::EXPIRES)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+        .map(|t| t.with_timezone(&Utc))
+        .filter(|t| *t > now)
+}
+
+pub mod lenient;
 pub mod refresh;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_1252_header_charset_is_transcoded() {
+        let bytes = to_utf8(b"caf\xe9", Some("text/xml; charset=windows-1252"));
+        assert_eq!(std::str::from_utf8(&bytes).unwrap(), "café");
+    }
+
+    #[test]
+    fn undeclared_latin_text_is_read_as_windows_1252() {
+        let doc = b"<?xml version=\"1.0\"?><rss version=\"2.0\"><channel>\
+<title>Cr\xe8me br\xfbl\xe9e \x80 5</title></channel></rss>";
+        let bytes = to_utf8(doc, Some("application/rss+xml"));
+        let feed = parser::parse(&bytes[..]).unwrap();
+        assert_eq!(feed.title.unwrap().content, "Crème brûlée € 5");
+    }
+
+    #[test]
+    fn xml_declaration_is_rewritten_after_transcoding() {
+        let doc = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><rss version=\"2.0\">\
+<channel><title>Gr\xfc\xdfe</title></channel></rss>";
+        let bytes = to_utf8(doc, None);
+        let text = std::str::from_utf8(&bytes).unwrap();
+        assert!(text.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+        let feed = parser::parse(&bytes[..]).unwrap();
+        assert_eq!(feed.title.unwrap().content, "Grüße");
+    }
+
+    #[test]
+    fn utf8_is_passed_through() {
+        let doc = "<rss><channel><title>café</title></channel></rss>".as_bytes();
+        assert!(matches!(to_utf8(doc, None), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn normalize_url_canonicalizes_equivalent_forms() {
+        let canonical = "https://example.com/feed";
+        for url in [
+            "https://example.com/feed",
+            "https://example.com/feed/",
+            "HTTPS://Example.COM/feed",
+            "https://example.com:443/feed",
+            "example.com/feed",
+            "  https://example.com/feed#top ",
+        ] {
+            assert_eq!(normalize_url(url), canonical, "{url}");
+        }
+        assert_eq!(
+            normalize_url("http://example.com:80/"),
+            "http://example.com/"
+        );
+        assert_eq!(
+            normalize_url("http://example.com:8080/a"),
+            "http://example.com:8080/a"
+        );
+        assert_eq!(normalize_url("not a url"), "not a url");
+    }
+
+    #[test]
+    fn same_feed_url_ignores_the_scheme() {
+        assert!(same_feed_url(
+            "http://example.com/feed/",
+            "https://EXAMPLE.com/feed"
+        ));
+        assert!(same_feed_url(
+            "example.com/feed",
+            "http://example.com:80/feed"
+        ));
+        assert!(!same_feed_url(
+            "https://example.com/feed",
+            "https://example.com/feed?page=2"
+        ));
+        assert!(!same_feed_url(
+            "https://example.com/a",
+            "https://example.org/a"
+        ));
+    }
+
+    /// Serve `response` to one request on a local port, returning the URL
+    /// and the raw request headers once they arrive.
+    async fn serve_once(response: String) -> (String, tokio::task::JoinHandle<String>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/feed", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let n = socket.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            socket.write_all(response.as_bytes()).await.unwrap();
+            socket.shutdown().await.unwrap();
+            String::from_utf8(request).unwrap().to_lowercase()
+        });
+        (url, server)
+    }
+
+    const FEED: &str = r#"<?xml version="1.0"?><rss version="2.0"><channel><title>Fresh</title><item><guid>1</guid><title>One</title></item></channel></rss>"#;
+
+    #[tokio::test]
+    async fn cached_headers_are_sent() {
+        let (url, server) =
+            serve_once("HTTP/1.1 304 Not Modified\r\nContent-Length: 0\r\n\r\n".into()).await;
+        let last = "Mon, 01 Jan 2024 00:00:00 GMT";
+        fetch_feed(&url, Some("\"v1\""), Some(last), false)
+            .await
+            .unwrap();
+        let request = server.await.unwrap();
+        assert!(request.contains("if-none-match: \"v1\"\r\n"), "{request}");
+        assert!(
+            request.contains(&format!("if-modified-since: {}\r\n", last.to_lowercase())),
+            "{request}"
+        );
+    }
+
+    #[tokio::test]
+    async fn no_conditional_headers_without_cache() {
+        let (url, server) =
+            serve_once("HTTP/1.1 304 Not Modified\r\nContent-Length: 0\r\n\r\n".into()).await;
+        fetch_feed(&url, None, None, false).await.unwrap();
+        let request = server.await.unwrap();
+        assert!(!request.contains("if-none-match"), "{request}");
+        assert!(!request.contains("if-modified-since"), "{request}");
+    }
+
+    #[tokio::test]
+    async fn not_modified_keeps_cached_headers() {
+        let (url, server) =
+            serve_once("HTTP/1.1 304 Not Modified\r\nContent-Length: 0\r\n\r\n".into()).await;
+        let outcome = fetch_feed(&url, Some("\"v1\""), Some("yesterday"), false)
+            .await
+            .unwrap();
+        server.await.unwrap();
+        assert!(outcome.feed.is_none());
+        assert_eq!(outcome.status, 304);
+        assert_eq!(outcome.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(outcome.last_modified.as_deref(), Some("yesterday"));
+    }
+
+    #[tokio::test]
+    async fn ok_returns_the_feed_and_new_headers() {
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nETag: \"v2\"\r\nLast-Modified: Tue, 02 Jan 2024 00:00:00 GMT\r\n\
+Content-Type: application/rss+xml\r\nContent-Length: {}\r\n\r\n{}",
+            FEED.len(),
+            FEED
+        );
+        let (url, server) = serve_once(response).await;
+        let outcome = fetch_feed(&url, Some("\"v1\""), Some("yesterday"), false)
+            .await
+            .unwrap();
+        server.await.unwrap();
+        assert_eq!(outcome.status, 200);
+        assert_eq!(outcome.etag.as_deref(), Some("\"v2\""));
+        assert_eq!(
+            outcome.last_modified.as_deref(),
+            Some("Tue, 02 Jan 2024 00:00:00 GMT")
+        );
+        let feed = outcome.feed.unwrap();
+        assert_eq!(feed.title.unwrap().content, "Fresh");
+        assert_eq!(feed.entries.len(), 1);
+    }
+}
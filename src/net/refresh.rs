@@ -1,15 +1,32 @@
 //! Background refresh manager for feeds.
 
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashMap, rc::Rc, sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
 
 use tokio::{
-    sync::{Mutex, mpsc},
+    sync::{Mutex, Semaphore, mpsc},
+    task::LocalSet,
     time,
 };
 
-use crate::data::Group;
+use crate::{
+    config::{DEFAULT_MAX_FAILURES, Net},
+    data::{Feed, Group},
+};
+
+use super::{FetchError, FetchOutcome, fetch_feed};
 
-use super::fetch_feed;
+/// Progress reported by the background refresh loop to the UI.
+#[derive(Debug, Clone)]
+pub enum RefreshStatus {
+    /// A refresh pass started.
+    Started,
+    /// Fetching `url` failed during the current pass.
+    Failed { url: String, error: String },
+    /// A refresh pass finished at `at`, having merged `new_items` items.
+    Finished { at: DateTime<Utc>, new_items: usize },
+}
 
 /// Spawn the refresh manager. The returned sender can be used to trigger a
 /// manual refresh (e.g. when the user presses F5).
@@ -33,22 +50,181 @@ pub fn spawn_refresh_manager(db: Arc<Mutex<Vec<Group>>>) -> mpsc::Sender<()> {
     tx
 }
 
+/// Record the result of fetching `feed` at `now` (a Unix timestamp),
+/// updating caching headers, health fields and the next poll time. The feed is disabled once
+/// `max_failures` consecutive fetches fail (0 never disables). Entries
+/// without a link are dropped when `skip_linkless` is set. Returns the number
+/// of new items merged.
+pub fn apply_fetch(
+    feed: &mut Feed,
+    result: Result<FetchOutcome, FetchError>,
+    now: i64,
+    max_failures: u32,
+    skip_linkless: bool,
+) -> usize {
+    match result {
+        Err(e) => {
+            feed.error_streak += 1;
+            if max_failures > 0 && feed.error_streak >= max_failures {
+                feed.disabled = true;
+            }
+            feed.last_status = e.status();
+            feed.last_error = Some(e.to_string());
+            feed.not_modified = false;
+            if let FetchError::RateLimited(limit) = &e {
+                feed.rate_limited_until = Some(limit.until.timestamp());
+            }
+            0
+        }
+        Ok(outcome) => {
+            feed.error_streak = 0;
+            feed.last_error = None;
+            feed.last_fetch = Some(now);
+            feed.last_status = Some(outcome.status);
+            feed.not_modified = outcome.feed.is_none();
+            feed.rate_limited_until = None;
+            feed.etag = outcome.etag;
+            feed.last_modified = outcome.last_modified;
+            if outcome.feed.is_some() {
+                feed.lenient_parse = outcome.lenient;
+                feed.update_interval = outcome.update_interval;
+            }
+            // Wait for whichever is later: the HTTP cache lifetime or the
+            // interval the feed asks for.
+            feed.next_fetch = outcome
+                .next_fetch
+                .map(|t| t.timestamp())
+                .max(feed.update_interval.map(|secs| now + secs));
+            outcome
+                .feed
+                .map_or(0, |parsed| feed.merge_items(parsed, skip_linkless))
+        }
+    }
+}
+
+/// A feed to fetch in a refresh pass, with its group index and cache
+/// validators.
+pub struct FetchTarget {
+    pub group: usize,
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+/// Fetch `targets` concurrently within the `limits`: at most
+/// `max_concurrent` requests overall and `per_host` per host, each followed
+/// by `per_host_delay_ms` before its slot is reused for that host. Results
+/// are passed to `on_result` as they arrive.
+pub async fn fetch_all(
+    targets: Vec<FetchTarget>,
+    limits: &Net,
+    lenient: bool,
+    mut on_result: impl FnMut(FetchTarget, Result<FetchOutcome, FetchError>),
+) {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let overall = Rc::new(Semaphore::new(limits.max_concurrent.max(1)));
+    let mut hosts: HashMap<String, Rc<Semaphore>> = HashMap::new();
+    let delay = Duration::from_millis(limits.per_host_delay_ms);
+    let local = LocalSet::new();
+    for target in targets {
+        let host = url::Url::parse(&target.url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .unwrap_or_else(|| target.url.clone());
+        let host = Rc::clone(
+            hosts
+                .entry(host)
+                .or_insert_with(|| Rc::new(Semaphore::new(limits.per_host.max(1)))),
+        );
+        let overall = Rc::clone(&overall);
+        let tx = tx.clone();
+        // The fetches share this thread; `run_until` below drives them.
+        local.spawn_local(async move {
+            // Take the host's slot first so waiting on a busy host doesn't
+            // hold up other hosts.
+            let _host = host.acquire().await;
+            let slot = overall.acquire().await;
+            let result = fetch_feed(
+                &target.url,
+                target.etag.as_deref(),
+                target.last_modified.as_deref(),
+                lenient,
+            )
+            .await;
+            drop(slot);
+            let _ = tx.send((target, result));
+            time::sleep(delay).await;
+        });
+    }
+    drop(tx);
+    local
+        .run_until(async {
+            while let Some((target, result)) = rx.recv().await {
+                on_result(target, result);
+            }
+        })
+        .await;
+}
+
 async fn refresh_all(db: &Arc<Mutex<Vec<Group>>>) {
     let mut guard = db.lock().await;
+    let now = chrono::Utc::now().timestamp();
     for group in guard.iter_mut() {
-        for feed in group.feeds.iter_mut() {
-            if let Ok((etag, last, Some(parsed))) = fetch_feed(
+        for feed in group.feeds.iter_mut().filter(|f| f.is_due(now)) {
+            let result = fetch_feed(
                 &feed.url,
                 feed.etag.as_deref(),
                 feed.last_modified.as_deref(),
+                false,
             )
-            .await
-            {
-                feed.etag = etag;
-                feed.last_modified = last;
-                feed.merge_items(parsed);
-            }
+            .await;
+            apply_fetch(feed, result, now, DEFAULT_MAX_FAILURES, false);
         }
         group.update_unread();
     }
 }
+
+/// Fetch a single feed on a background thread and merge the result into
+/// `groups`. The feed is located by group index and URL so it is skipped if
+/// it was moved or deleted in the meantime.
+pub fn spawn_feed_refresh(
+    groups: Arc<std::sync::Mutex<Vec<Group>>>,
+    group: usize,
+    url: String,
+    max_failures: u32,
+    lenient: bool,
+    skip_linkless: bool,
+) {
+    std::thread::spawn(move || {
+        let (etag, last) = {
+            let guard = groups.lock().unwrap();
+            match guard
+                .get(group)
+                .and_then(|g| g.feeds.iter().find(|f| f.url == url))
+            {
+                Some(feed) => (feed.etag.clone(), feed.last_modified.clone()),
+                None => return,
+            }
+        };
+        let Ok(rt) = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        else {
+            return;
+        };
+        let result = rt.block_on(fetch_feed(&url, etag.as_deref(), last.as_deref(), lenient));
+        let mut guard = groups.lock().unwrap();
+        if let Some(g) = guard.get_mut(group) {
+            if let Some(feed) = g.feeds.iter_mut().find(|f| f.url == url) {
+                apply_fetch(
+                    feed,
+                    result,
+                    chrono::Utc::now().timestamp(),
+                    max_failures,
+                    skip_linkless,
+                );
+            }
+            g.update_unread();
+        }
+    });
+}
@@ -0,0 +1,16 @@
+//! Clipboard access through the terminal.
+//!
+//! Text is sent with the OSC 52 escape sequence, which most terminal
+//! emulators (and tmux with `set-clipboard on`) forward to the system
+//! clipboard, including over SSH.
+
+use std::io::{self, Write};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+/// Put `text` on the clipboard.
+pub fn copy(text: &str) -> io::Result<()> {
+    let mut out = io::stdout();
+    write!(out, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    out.flush()
+}
@@ -0,0 +1,188 @@
+//! Jump box opened with Ctrl+g: finds items, feeds and groups by name across
+//! the whole database and moves the selection to the chosen one.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+use super::fuzzy_match;
+use crate::data::Group;
+
+/// Item matches listed at most; feeds and groups are few enough to list all.
+const MAX_ITEM_HITS: usize = 50;
+
+/// Where a result leads.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    Item {
+        group: usize,
+        feed: usize,
+        id: String,
+    },
+    Feed {
+        group: usize,
+        feed: usize,
+    },
+    Group(usize),
+}
+
+/// What a key press did to the jump box.
+pub enum Outcome {
+    Stay,
+    Close,
+    Go(Target),
+}
+
+/// A result row.
+pub struct Hit {
+    pub label: String,
+    pub target: Target,
+}
+
+/// Query and results of the open jump box.
+#[derive(Default)]
+pub struct Jump {
+    pub query: String,
+    /// Item results first, then feeds, then groups.
+    pub hits: Vec<Hit>,
+    pub selected: usize,
+}
+
+impl Jump {
+    /// Recompute the results for the current query. Items whose titles
+    /// contain the query come before those that only match fuzzily, newest
+    /// first within each.
+    pub fn update(&mut self, groups: &[Group]) {
+        self.selected = 0;
+        self.hits.clear();
+        let query = self.query.trim();
+        if query.is_empty() {
+            return;
+        }
+        let lower = query.to_lowercase();
+        let mut items: Vec<(bool, i64, Hit)> = Vec::new();
+        for (g, group) in groups.iter().enumerate() {
+            for (f, feed) in group.feeds.iter().enumerate() {
+                for item in &feed.items {
+                    if item.hidden || !fuzzy_match(query, &item.title) {
+                        continue;
+                    }
+                    items.push((
+                        !item.title.to_lowercase().contains(&lower),
+                        item.timestamp,
+                        Hit {
+                            label: format!("{}  ({})", item.title, feed.title),
+                            target: Target::Item {
+                                group: g,
+                                feed: f,
+                                id: item.id.clone(),
+                            },
+                        },
+                    ));
+                }
+            }
+        }
+        items.sort_by_key(|(fuzzy, timestamp, _)| (*fuzzy, std::cmp::Reverse(*timestamp)));
+        self.hits
+            .extend(items.into_iter().take(MAX_ITEM_HITS).map(|(_, _, hit)| hit));
+        for (g, group) in groups.iter().enumerate() {
+            for (f, feed) in group.feeds.iter().enumerate() {
+                if fuzzy_match(query, &feed.title) {
+                    self.hits.push(Hit {
+                        label: format!("{}  ({})", feed.title, group.name),
+                        target: Target::Feed { group: g, feed: f },
+                    });
+                }
+            }
+        }
+        for (g, group) in groups.iter().enumerate() {
+            if fuzzy_match(query, &group.name) {
+                self.hits.push(Hit {
+                    label: group.name.clone(),
+                    target: Target::Group(g),
+                });
+            }
+        }
+    }
+
+    /// Edit the query or move through the results.
+    pub fn handle_key(&mut self, key: KeyEvent, groups: &[Group]) -> Outcome {
+        match key.code {
+            KeyCode::Esc => return Outcome::Close,
+            KeyCode::Enter => {
+                if let Some(hit) = self.hits.get(self.selected) {
+                    return Outcome::Go(hit.target.clone());
+                }
+            }
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.selected = (self.selected + 1).min(self.hits.len().saturating_sub(1))
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.update(groups);
+            }
+            KeyCode::Char(c)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.query.push(c);
+                self.update(groups);
+            }
+            _ => {}
+        }
+        Outcome::Stay
+    }
+
+    pub fn draw(&self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(area);
+        let input = Paragraph::new(self.query.as_str()).block(
+            Block::default()
+                .title("Go to item, feed or group [Enter: go, Esc: close]")
+                .borders(Borders::ALL),
+        );
+        let section = |target: &Target| match target {
+            Target::Item { .. } => "Items",
+            Target::Feed { .. } => "Feeds",
+            Target::Group(_) => "Groups",
+        };
+        let entries: Vec<ListItem> = self
+            .hits
+            .iter()
+            .enumerate()
+            .map(|(n, hit)| {
+                let mut lines = Vec::new();
+                let name = section(&hit.target);
+                if n == 0 || section(&self.hits[n - 1].target) != name {
+                    lines.push(Line::from(Span::styled(
+                        name,
+                        Style::default()
+                            .fg(Color::DarkGray)
+                            .add_modifier(Modifier::BOLD),
+                    )));
+                }
+                lines.push(Line::from(format!("  {}", hit.label)));
+                ListItem::new(lines)
+            })
+            .collect();
+        let list = List::new(entries)
+            .block(Block::default().borders(Borders::ALL))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default();
+        if !self.hits.is_empty() {
+            state.select(Some(self.selected));
+        }
+        f.render_widget(Clear, area);
+        f.render_widget(input, chunks[0]);
+        f.render_stateful_widget(list, chunks[1], &mut state);
+    }
+}
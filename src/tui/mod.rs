@@ -2,35 +2,60 @@
 
 //! Terminal user interface components built with ratatui and crossterm.
 
+mod clipboard;
+mod jump;
+mod reader;
+
+use std::borrow::Cow;
 use std::time::{Duration, Instant};
 use std::{
-    io::{self, Write},
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
     process::Command,
-    sync::{Arc, Mutex, mpsc::Receiver},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+    },
 };
 
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, Local, TimeZone, Utc};
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    cursor::Show,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    event::{DisableBracketedPaste, EnableBracketedPaste},
     execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+    terminal::{
+        EnterAlternateScreen, LeaveAlternateScreen, SetTitle, disable_raw_mode, enable_raw_mode,
+    },
 };
 use ratatui::{
     Frame, Terminal,
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout, Rect},
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
 };
 
+use serde::{Deserialize, Serialize};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use self::jump::{Jump, Outcome, Target};
+use self::reader::Reader;
 use crate::{
-    config::Config,
+    config::{
+        self, Config, ItemDensity, Opener, OpenerMode, PreviewPosition, ReadFilter, SortOrder,
+        Theme,
+    },
     data::{self, Feed, Group, Item},
+    net::{self, refresh::RefreshStatus},
 };
 
 /// Application focusable panes.
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub enum Pane {
     #[default]
     Groups,
@@ -40,9 +65,148 @@ pub enum Pane {
     Queue,
 }
 
+/// Navigation position persisted between runs.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Session {
+    pub selected_group: usize,
+    pub selected_feed: usize,
+    pub focus: Pane,
+}
+
+impl Session {
+    /// Load the last saved session, if any.
+    pub fn load() -> Option<Self> {
+        let path = data::session_path()?;
+        let content = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Persist the session next to the database, unless in read-only mode.
+    pub fn save(&self) -> io::Result<()> {
+        if config::read_only() {
+            return Ok(());
+        }
+        let path = data::session_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "data dir"))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        std::fs::write(path, json)
+    }
+}
+
+/// How the Preview pane renders an item's description.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PreviewMode {
+    /// HTML stripped down to plain text.
+    #[default]
+    Text,
+    /// The description exactly as delivered by the feed.
+    Raw,
+}
+
+impl PreviewMode {
+    fn next(self) -> Self {
+        match self {
+            PreviewMode::Text => PreviewMode::Raw,
+            PreviewMode::Raw => PreviewMode::Text,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            PreviewMode::Text => "text",
+            PreviewMode::Raw => "raw",
+        }
+    }
+}
+
 /// Action to perform when an input popup is submitted.
 pub enum InputAction {
     AddGroup,
+    /// Rename the group at this index.
+    RenameGroup(usize),
+    /// Subscribe to the entered URL; asks for the group next.
+    AddFeed,
+    /// Add the feed at `url` to the entered group, creating it if needed.
+    AddFeedToGroup {
+        url: String,
+    },
+    /// Live item search; `previous` is restored if the popup is cancelled.
+    Search {
+        previous: String,
+    },
+    /// Live feed title filter; `previous` is restored if cancelled.
+    FeedSearch {
+        previous: String,
+    },
+    /// Mark items older than the entered age read in a group, or in one of
+    /// its feeds when `feed` is set.
+    MarkReadOlder {
+        group: usize,
+        feed: Option<usize>,
+    },
+    /// Merge the subscriptions in the OPML file at the entered path.
+    ImportOpml,
+    /// Write the whole database as JSON to the entered path.
+    ExportJson,
+    /// Merge the JSON backup at the entered path.
+    ImportJson,
+    /// Subscribe to every URL in the entered (or pasted) text, separated by
+    /// whitespace, adding them to `group`.
+    AddFeeds {
+        group: String,
+    },
+    /// Change the URL of the feed currently at `url` in `group`.
+    EditFeedUrl {
+        group: usize,
+        url: String,
+    },
+    /// Write the item with this id to the entered path.
+    ExportItem {
+        group: usize,
+        feed: usize,
+        id: String,
+    },
+}
+
+/// A bulk or destructive action that can be previewed before it runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BulkAction {
+    MarkAllRead,
+    MarkGroupRead(usize),
+    OpenGroupUnread(usize),
+    OpenFeedUnread(usize, usize),
+    OpenQueue,
+    /// Quit although items are queued.
+    Quit,
+    DeleteGroup(usize),
+    DeleteFeed(usize, usize),
+}
+
+/// Confirmation popup listing what a [`BulkAction`] will affect.
+pub struct BulkPreview {
+    pub action: BulkAction,
+    pub title: String,
+    pub lines: Vec<String>,
+    /// Number of items affected; 1 for a delete.
+    pub count: usize,
+}
+
+/// Find-as-you-type in the Groups or Feeds pane, started with `'`.
+pub struct TypeAhead {
+    pub text: String,
+    pub last_key: Instant,
+}
+
+/// A single-feed retry started with `R`, with the feed's fetch state when it
+/// began so the outcome can be told apart.
+pub struct Retry {
+    pub group: usize,
+    pub url: String,
+    pub last_fetch: Option<i64>,
+    pub error_streak: u32,
 }
 
 /// Transient state for text input popups.
@@ -65,20 +229,96 @@ pub struct AppState {
     pub selected_item: usize,
     pub last_refresh: Option<DateTime<Utc>>,
     pub new_items: usize,
-    pub status_rx: Receiver<(DateTime<Utc>, usize)>,
+    pub status_rx: Receiver<RefreshStatus>,
+    /// Whether the background refresh loop is mid-pass.
+    pub refreshing: bool,
+    /// Feed URL and message of the latest fetch failure in the current or
+    /// last refresh pass.
+    pub last_error: Option<(String, String)>,
     pub input_popup: Option<InputPopup>,
+    /// Bulk action waiting for Enter/y or Esc/n.
+    pub bulk_preview: Option<BulkPreview>,
+    /// Id of the item whose description is shown in full despite
+    /// `preview_max_chars`.
+    pub preview_full: Option<String>,
+    /// Article open in the built-in reader.
+    pub reader: Option<Reader>,
+    /// Jump box opened with Ctrl+g.
+    pub jump: Option<Jump>,
+    /// When the schedule was last checked; a `queue_open_at` time passed
+    /// since then is due.
+    pub schedule_checked: DateTime<Local>,
+    /// The scheduled queue open is due but waits for open popups to close.
+    pub queue_open_due: bool,
+    /// Unread total last written to the terminal title.
+    pub title_unread: Option<usize>,
+    pub preview_mode: PreviewMode,
+    /// Only show items tagged with this category.
+    pub category_filter: Option<String>,
+    /// One-off message shown in the status bar until the next key press.
+    pub message: Option<String>,
+    /// Start of a visual selection in the Items pane, as a visible position.
+    pub visual_anchor: Option<usize>,
+    /// In the tree view, whether the cursor is on a feed row rather than on
+    /// the selected group's header.
+    pub tree_on_feed: bool,
+    /// Group index and URL of the feed shown in the Items pane, used to
+    /// update its last-seen marker once the user moves on.
+    pub viewing: Option<(usize, String)>,
+    /// Filter applied to feed titles in the Feeds pane.
+    pub feed_search: String,
+    /// Cursor in the Queue pane's list.
+    pub queue_selected: usize,
+    /// Whether the Queue pane shows starred items instead of the queue.
+    pub show_starred: bool,
+    /// Category sections folded to their header in feeds grouped by
+    /// category; `""` stands for uncategorized items.
+    pub collapsed_categories: HashSet<String>,
+    /// Whether hidden items are listed again so they can be restored.
+    pub show_hidden: bool,
+    /// Ids of items whose similar-titled variants are listed, expanded with
+    /// `e` when `collapse_similar_titles` is set.
+    pub expanded_variants: HashSet<String>,
+    /// Items listed per feed URL once `L` loaded more than `page_size`.
+    pub visible_limits: HashMap<String, usize>,
+    /// Letters typed so far in link hint mode, started with `f` in the
+    /// Preview pane.
+    pub link_hints: Option<String>,
+    /// Whether the database may have changed since it was last written.
+    pub dirty: bool,
+    /// Draw without colors and with ASCII markers, for `--no-color`,
+    /// `NO_COLOR` or output that isn't a terminal.
+    pub plain: bool,
+    /// Draw on the alternate screen; off with `--no-altscreen` so output and
+    /// panics stay visible after exit.
+    pub alt_screen: bool,
+    /// File that gets a JSON summary after each refresh, for status bars.
+    pub status_file: Option<PathBuf>,
+    /// Retry whose outcome is reported in the status bar once it arrives.
+    pub retrying: Option<Retry>,
+    pub last_save: Instant,
+    /// Active type-ahead find; letters extend it instead of running commands.
+    pub type_ahead: Option<TypeAhead>,
 }
 
 impl AppState {
+    /// Create an application state with no background refresh attached, e.g.
+    /// to drive the key handlers or render `ui` without a refresh thread.
+    pub fn detached(config: Config, groups: Arc<Mutex<Vec<Group>>>) -> Self {
+        let (_, status_rx) = mpsc::channel();
+        Self::new(config, groups, status_rx)
+    }
+
     /// Create a new application state with loaded configuration and groups.
     pub fn new(
         config: Config,
         groups: Arc<Mutex<Vec<Group>>>,
-        status_rx: Receiver<(DateTime<Utc>, usize)>,
+        status_rx: Receiver<RefreshStatus>,
     ) -> Self {
-        Self {
+        let queue = queued_items(&groups.lock().unwrap());
+        let mut app = Self {
             focus: Pane::Groups,
-            queue: Vec::new(),
+            queue,
             search: String::new(),
             show_help: false,
             config,
@@ -89,37 +329,574 @@ impl AppState {
             last_refresh: None,
             new_items: 0,
             status_rx,
+            refreshing: false,
+            last_error: None,
             input_popup: None,
+            bulk_preview: None,
+            preview_full: None,
+            reader: None,
+            jump: None,
+            schedule_checked: Local::now(),
+            queue_open_due: false,
+            title_unread: None,
+            preview_mode: PreviewMode::default(),
+            category_filter: None,
+            message: None,
+            visual_anchor: None,
+            tree_on_feed: false,
+            viewing: None,
+            feed_search: String::new(),
+            queue_selected: 0,
+            show_starred: false,
+            collapsed_categories: HashSet::new(),
+            show_hidden: false,
+            expanded_variants: HashSet::new(),
+            visible_limits: HashMap::new(),
+            link_hints: None,
+            dirty: false,
+            plain: false,
+            alt_screen: true,
+            status_file: None,
+            retrying: None,
+            last_save: Instant::now(),
+            type_ahead: None,
+        };
+        if app.config.ui.restore_session
+            && let Some(session) = Session::load()
+        {
+            app.restore_session(session);
+        }
+        app
+    }
+
+    /// Apply a saved session, clamping indices in case feeds changed since.
+    fn restore_session(&mut self, session: Session) {
+        let groups = self.groups.lock().unwrap();
+        self.selected_group = session.selected_group.min(groups.len().saturating_sub(1));
+        let feeds = groups.get(self.selected_group).map_or(0, |g| g.feeds.len());
+        self.selected_feed = session.selected_feed.min(feeds.saturating_sub(1));
+        self.focus = match session.focus {
+            Pane::Queue => Pane::Items,
+            Pane::Preview if !self.preview_visible() => Pane::Items,
+            pane => pane,
+        };
+    }
+
+    /// Mark the feed being viewed as seen and forget it.
+    fn leave_feed(&mut self) {
+        let Some((g, url)) = self.viewing.take() else {
+            return;
+        };
+        let mut groups = self.groups.lock().unwrap();
+        if let Some(feed) = groups
+            .get_mut(g)
+            .and_then(|group| group.feeds.iter_mut().find(|f| f.url == url))
+        {
+            feed.mark_seen();
+            self.dirty = true;
+        }
+    }
+
+    /// Report the outcome of a retry started with `R` once the feed's fetch
+    /// state changes.
+    fn poll_retry(&mut self) {
+        let Some(retry) = &self.retrying else {
+            return;
+        };
+        let message = {
+            let groups = self.groups.lock().unwrap();
+            match groups
+                .get(retry.group)
+                .and_then(|g| g.feeds.iter().find(|f| f.url == retry.url))
+            {
+                None => None,
+                Some(feed)
+                    if feed.last_fetch == retry.last_fetch
+                        && feed.error_streak == retry.error_streak =>
+                {
+                    return;
+                }
+                Some(feed) => Some(match &feed.last_error {
+                    Some(error) => format!("retry of '{}' failed: {}", feed.title, error),
+                    None => format!(
+                        "'{}' fetched: {} items ({} unread)",
+                        feed.title,
+                        feed.total_count(),
+                        feed.unread_count()
+                    ),
+                }),
+            }
+        };
+        self.retrying = None;
+        if message.is_some() {
+            self.message = message;
+            self.dirty = true;
+        }
+    }
+
+    /// Open the queue once `queue_open_at` passes, at most once a day and
+    /// only after any popup is closed. An empty queue is left alone.
+    fn poll_schedule(&mut self) {
+        let now = Local::now();
+        if let Some(at) = self.config.schedule.queue_open_time()
+            && let Some(target) = now
+                .date_naive()
+                .and_time(at)
+                .and_local_timezone(Local)
+                .single()
+            && self.schedule_checked < target
+            && target <= now
+        {
+            self.queue_open_due = true;
+        }
+        self.schedule_checked = now;
+        let busy = self.input_popup.is_some()
+            || self.bulk_preview.is_some()
+            || self.reader.is_some()
+            || self.jump.is_some();
+        if !self.queue_open_due || busy {
+            return;
+        }
+        self.queue_open_due = false;
+        if !self.queue.is_empty() {
+            self.message = Some(format!(
+                "scheduled: opening {} queued items",
+                self.queue.len()
+            ));
+            request_bulk(self, BulkAction::OpenQueue);
+        }
+    }
+
+    /// Notice when the selection moves to another feed, marking the previous
+    /// one as seen.
+    fn track_viewed_feed(&mut self) {
+        let current = {
+            let groups = self.groups.lock().unwrap();
+            groups
+                .get(self.selected_group)
+                .and_then(|g| g.feeds.get(self.selected_feed))
+                .map(|f| (self.selected_group, f.url.clone()))
+        };
+        if current != self.viewing {
+            self.leave_feed();
+            self.viewing = current;
+        }
+    }
+
+    /// Keep the selection on groups, feeds and items that still exist, e.g.
+    /// after deleting the last one in a list, and move focus back to Groups
+    /// when the selected group has no feeds left to show.
+    fn clamp_selection(&mut self) {
+        let (group_count, feed_count, item_count) = {
+            let groups = self.groups.lock().unwrap();
+            let group = groups.get(self.selected_group.min(groups.len().saturating_sub(1)));
+            let feed_count = group.map_or(0, |g| g.feeds.len());
+            let item_count = group
+                .and_then(|g| {
+                    g.feeds
+                        .get(self.selected_feed.min(feed_count.saturating_sub(1)))
+                        .map(|f| feed_indices(self, self.config.ui.read_filter(), g, f).len())
+                })
+                .unwrap_or_default();
+            (groups.len(), feed_count, item_count)
+        };
+        self.selected_group = self.selected_group.min(group_count.saturating_sub(1));
+        self.selected_feed = self.selected_feed.min(feed_count.saturating_sub(1));
+        self.selected_item = self.selected_item.min(item_count.saturating_sub(1));
+        if feed_count == 0 {
+            self.tree_on_feed = false;
+            if matches!(self.focus, Pane::Feeds | Pane::Items | Pane::Preview) {
+                self.focus = Pane::Groups;
+            }
+        }
+    }
+
+    /// Change the feed filter, moving the selection to the first match if
+    /// the selected feed no longer matches.
+    fn set_feed_search(&mut self, query: String) {
+        self.feed_search = query;
+        let groups = self.groups.lock().unwrap();
+        let Some(group) = groups.get(self.selected_group) else {
+            return;
+        };
+        let shown = feed_matches(&self.feed_search, &group.feeds);
+        if !shown.contains(&self.selected_feed)
+            && let Some(&first) = shown.first()
+        {
+            self.selected_feed = first;
+            self.selected_item = 0;
+        }
+    }
+
+    /// Whether the layout leaves room for the Preview pane.
+    fn preview_visible(&self) -> bool {
+        self.config.ui.show_preview
+            && self.config.ui.layout.preview_position != PreviewPosition::Hidden
+    }
+
+    /// Visible positions covered by the visual selection, if one is active.
+    fn visual_range(&self, len: usize) -> Option<std::ops::RangeInclusive<usize>> {
+        let anchor = self.visual_anchor?;
+        if len == 0 {
+            return None;
+        }
+        let a = anchor.min(len - 1);
+        let b = self.selected_item.min(len - 1);
+        Some(a.min(b)..=a.max(b))
+    }
+
+    /// Snapshot the current navigation position.
+    fn session(&self) -> Session {
+        Session {
+            selected_group: self.selected_group,
+            selected_feed: self.selected_feed,
+            focus: self.focus,
+        }
+    }
+}
+
+/// Number of items that may be opened at once without asking, even when
+/// `confirm_bulk_open` is off.
+const BULK_OPEN_LIMIT: usize = 20;
+
+/// Describe what `action` would do, or `None` if it affects nothing.
+fn bulk_preview(action: BulkAction, groups: &[Group], queue: &[Item]) -> Option<BulkPreview> {
+    let feed_line = |feed: &Feed| format!("{:>5}  {}", feed.unread_count(), feed.title);
+    let (title, lines, count) = match action {
+        BulkAction::MarkGroupRead(g) | BulkAction::OpenGroupUnread(g) => {
+            let group = groups.get(g)?;
+            let count = group.feeds.iter().map(Feed::unread_count).sum();
+            let verb = if matches!(action, BulkAction::MarkGroupRead(_)) {
+                "Mark read"
+            } else {
+                "Open"
+            };
+            let lines = group
+                .feeds
+                .iter()
+                .filter(|f| f.unread_count() > 0)
+                .map(feed_line)
+                .collect();
+            (
+                format!("{} {} unread items in '{}'?", verb, count, group.name),
+                lines,
+                count,
+            )
+        }
+        BulkAction::OpenFeedUnread(g, f) => {
+            let feed = groups.get(g)?.feeds.get(f)?;
+            let count = feed.unread_count();
+            (
+                format!("Open {} unread items in '{}'?", count, feed.title),
+                vec![feed_line(feed)],
+                count,
+            )
+        }
+        BulkAction::MarkAllRead => {
+            let count = data::total_unread(groups);
+            let lines = groups
+                .iter()
+                .filter(|g| g.unread_count > 0)
+                .map(|g| format!("{:>5}  {}", g.unread_count, g.name))
+                .collect();
+            (
+                format!("Mark read all {} unread items?", count),
+                lines,
+                count,
+            )
+        }
+        BulkAction::OpenQueue => (
+            format!("Open {} queued items?", queue.len()),
+            queue
+                .iter()
+                .map(|i| format!("       {}", i.title))
+                .collect(),
+            queue.len(),
+        ),
+        BulkAction::Quit => (
+            format!("You have {} queued items — quit anyway?", queue.len()),
+            queue
+                .iter()
+                .map(|i| format!("       {}", i.title))
+                .collect(),
+            queue.len(),
+        ),
+        BulkAction::DeleteGroup(g) => {
+            let group = groups.get(g)?;
+            (
+                format!(
+                    "Delete group '{}' and its {} feeds?",
+                    group.name,
+                    group.feeds.len()
+                ),
+                group.feeds.iter().map(feed_line).collect(),
+                1,
+            )
+        }
+        BulkAction::DeleteFeed(g, f) => {
+            let feed = groups.get(g)?.feeds.get(f)?;
+            (
+                format!(
+                    "Delete feed '{}' and its {} items?",
+                    feed.title,
+                    feed.total_count()
+                ),
+                vec![feed_line(feed)],
+                1,
+            )
+        }
+    };
+    (count > 0).then_some(BulkPreview {
+        action,
+        title,
+        lines,
+        count,
+    })
+}
+
+/// Run `action` immediately.
+fn run_bulk(action: BulkAction, groups: &mut Vec<Group>, queue: &mut Vec<Item>, config: &Config) {
+    let opener = &config.opener;
+    match action {
+        BulkAction::MarkAllRead => groups.iter_mut().for_each(mark_group_read),
+        BulkAction::MarkGroupRead(g) => {
+            if let Some(group) = groups.get_mut(g) {
+                mark_group_read(group);
+            }
+        }
+        BulkAction::OpenGroupUnread(g) => {
+            if let Some(group) = groups.get_mut(g) {
+                open_unread_group(group, opener);
+            }
+        }
+        BulkAction::OpenFeedUnread(g, f) => {
+            if let Some(group) = groups.get_mut(g)
+                && let Some(feed) = group.feeds.get_mut(f)
+            {
+                open_unread_feed(feed, opener);
+                group.update_unread();
+            }
+        }
+        BulkAction::OpenQueue => {
+            let marks_read = config.ui.queue_open_marks_read;
+            let clears = config.ui.queue_open_clears;
+            for (link, background) in queued_links(groups, queue, opener) {
+                open_link(&opener.command, background, &link);
+            }
+            let ids: HashSet<&str> = queue.iter().map(|i| i.id.as_str()).collect();
+            for group in groups.iter_mut() {
+                let mut touched = false;
+                for item in group.feeds.iter_mut().flat_map(|f| &mut f.items) {
+                    if ids.contains(item.id.as_str()) {
+                        if marks_read {
+                            item.set_read(true);
+                        }
+                        if clears {
+                            item.set_queued(false);
+                        }
+                        touched = true;
+                    }
+                }
+                if touched {
+                    group.update_unread();
+                }
+            }
+            if clears {
+                queue.clear();
+            }
+        }
+        // The caller exits; the queue is saved as is.
+        BulkAction::Quit => {}
+        BulkAction::DeleteGroup(g) => {
+            if g < groups.len() {
+                groups.remove(g);
+            }
+        }
+        BulkAction::DeleteFeed(g, f) => {
+            if let Some(group) = groups.get_mut(g)
+                && f < group.feeds.len()
+            {
+                group.feeds.remove(f);
+                group.update_unread();
+            }
+        }
+    }
+}
+
+/// Link of each queued item still in a feed, in queue order and once per
+/// item, with whether it opens in the background as set for the first feed
+/// holding it.
+fn queued_links(groups: &[Group], queue: &[Item], opener: &Opener) -> Vec<(String, bool)> {
+    let ids: HashSet<&str> = queue.iter().map(|i| i.id.as_str()).collect();
+    let mut found: HashMap<&str, (String, bool)> = HashMap::new();
+    for feed in groups.iter().flat_map(|g| &g.feeds) {
+        let background = feed.open_in_background.unwrap_or(opener.background);
+        for item in feed.items.iter().filter(|i| ids.contains(i.id.as_str())) {
+            found
+                .entry(item.id.as_str())
+                .or_insert_with(|| (item.link.clone(), background));
+        }
+    }
+    queue
+        .iter()
+        .filter_map(|queued| found.remove(queued.id.as_str()))
+        .collect()
+}
+
+/// Items saved as queued, least recently changed first, to rebuild the
+/// queue at startup.
+fn queued_items(groups: &[Group]) -> Vec<Item> {
+    let mut items: Vec<Item> = groups
+        .iter()
+        .flat_map(|g| &g.feeds)
+        .flat_map(|f| &f.items)
+        .filter(|i| i.queued)
+        .cloned()
+        .collect();
+    items.sort_by_key(|i| i.flags_updated);
+    items
+}
+
+/// Add `item` to the queue and flag it as queued, unless the queue already
+/// holds it. Returns whether it was added.
+fn enqueue(queue: &mut Vec<Item>, item: &mut Item) -> bool {
+    if queue.iter().any(|queued| queued.id == item.id) {
+        return false;
+    }
+    item.set_queued(true);
+    queue.push(item.clone());
+    true
+}
+
+/// Queue every unread item in `feeds` that isn't queued yet, returning how
+/// many were added.
+fn queue_unread(feeds: &mut [Feed], queue: &mut Vec<Item>) -> usize {
+    let mut added = 0;
+    for item in feeds.iter_mut().flat_map(|f| &mut f.items) {
+        if item.is_unread() && enqueue(queue, item) {
+            added += 1;
         }
     }
+    added
 }
 
-#[deprecated]
-fn prompt(msg: &str) -> Option<String> {
-    disable_raw_mode().ok()?;
-    print!("{} ", msg);
-    let _ = io::stdout().flush();
-    let mut input = String::new();
-    if io::stdin().read_line(&mut input).ok()? == 0 {
-        let _ = enable_raw_mode();
-        return None;
+/// Status message after bulk-queueing, warning when opening the queue will
+/// ask first because it holds more than [`BULK_OPEN_LIMIT`] items.
+fn queued_message(added: usize, total: usize) -> String {
+    if total > BULK_OPEN_LIMIT {
+        format!(
+            "queued {} items; {} in queue, opening will ask first",
+            added, total
+        )
+    } else {
+        format!("queued {} items; {} in queue", added, total)
     }
-    let _ = enable_raw_mode();
-    let s = input.trim().to_string();
-    if s.is_empty() { None } else { Some(s) }
 }
 
-fn confirm(msg: &str) -> bool {
-    if let Some(ans) = prompt(&format!("{} [y/N]", msg)) {
-        matches!(ans.to_lowercase().as_str(), "y" | "yes")
+/// Run `action`, first showing a preview popup when it marks items read, or
+/// opens items and `confirm_bulk_open` is on or more than
+/// [`BULK_OPEN_LIMIT`] items would open. Must be called without the groups
+/// lock held.
+fn request_bulk(app: &mut AppState, action: BulkAction) {
+    let mut groups = app.groups.lock().unwrap();
+    let Some(preview) = bulk_preview(action, &groups, &app.queue) else {
+        return;
+    };
+    let confirm = match action {
+        BulkAction::MarkAllRead | BulkAction::MarkGroupRead(_) => true,
+        BulkAction::DeleteGroup(_) | BulkAction::DeleteFeed(..) => app.config.ui.confirm_delete,
+        _ => app.config.ui.confirm_bulk_open || preview.count > BULK_OPEN_LIMIT,
+    };
+    if confirm {
+        app.bulk_preview = Some(preview);
     } else {
-        false
+        run_bulk(action, &mut groups, &mut app.queue, &app.config);
+        drop(groups);
+        finish_bulk(app, action);
+    }
+}
+
+/// Move focus and selection after `action` ran: to the items once the queue
+/// is opened, and back onto existing entries after a delete.
+fn finish_bulk(app: &mut AppState, action: BulkAction) {
+    app.dirty |= action != BulkAction::Quit;
+    match action {
+        BulkAction::OpenQueue => app.focus = Pane::Items,
+        BulkAction::DeleteGroup(_) => {
+            app.selected_feed = 0;
+            app.selected_item = 0;
+            app.clamp_selection();
+        }
+        BulkAction::DeleteFeed(..) => {
+            app.selected_item = 0;
+            app.clamp_selection();
+        }
+        _ => {}
+    }
+}
+
+/// Keys for the bulk action preview: Enter/y runs it, Esc/n cancels.
+/// Returns `true` when a quit was confirmed.
+fn handle_bulk_preview_key(code: KeyCode, app: &mut AppState) -> bool {
+    let Some(preview) = app.bulk_preview.take() else {
+        return false;
+    };
+    match code {
+        KeyCode::Enter | KeyCode::Char('y') => {
+            let mut groups = app.groups.lock().unwrap();
+            run_bulk(preview.action, &mut groups, &mut app.queue, &app.config);
+            drop(groups);
+            finish_bulk(app, preview.action);
+            return preview.action == BulkAction::Quit;
+        }
+        KeyCode::Esc | KeyCode::Char('n') => {}
+        _ => app.bulk_preview = Some(preview),
+    }
+    false
+}
+
+/// Whether a quit key should exit now. With `confirm_quit_with_queue` and a
+/// non-empty queue the first press asks instead; pressing it again while
+/// asked quits.
+fn quit_now(app: &mut AppState) -> bool {
+    let asked = app
+        .bulk_preview
+        .as_ref()
+        .is_some_and(|p| p.action == BulkAction::Quit);
+    if asked || !app.config.ui.confirm_quit_with_queue {
+        return true;
+    }
+    let preview = bulk_preview(BulkAction::Quit, &app.groups.lock().unwrap(), &app.queue);
+    match preview {
+        Some(preview) => {
+            app.input_popup = None;
+            app.bulk_preview = Some(preview);
+            false
+        }
+        None => true,
+    }
+}
+
+/// Open a single item's link with the configured opener, or return a reader
+/// for it when `opener.mode` is internal.
+fn open_or_read(opener: &Opener, feed: &Feed, title: &str, url: &str) -> Option<Reader> {
+    match opener.mode {
+        OpenerMode::External => {
+            let background = feed.open_in_background.unwrap_or(opener.background);
+            open_link(&opener.command, background, url);
+            None
+        }
+        OpenerMode::Internal => Some(Reader::load(title, url)),
     }
 }
 
-fn open_link(opener: &str, url: &str) {
+/// Open `url` with `opener`, or with the system handler when it is empty.
+/// `background` only applies to the system handler: it decides whether the
+/// browser is raised or the terminal keeps focus. Custom commands control
+/// focus themselves.
+fn open_link(opener: &str, background: bool, url: &str) {
     if opener.trim().is_empty() {
-        let _ = open::that_in_background(url);
+        open_system(background, url);
         return;
     }
 
@@ -146,13 +923,32 @@ fn open_link(opener: &str, url: &str) {
         }
         let _ = command.spawn();
     } else {
+        open_system(background, url);
+    }
+}
+
+fn open_system(background: bool, url: &str) {
+    if background {
         let _ = open::that_in_background(url);
+    } else {
+        let _ = open::that_detached(url);
+    }
+}
+
+/// Resolve the `n`th (1-based) link of an item, falling back to the item's
+/// own link when the body has none.
+fn item_link(item: &Item, n: usize) -> Option<&str> {
+    if item.links.is_empty() {
+        return (n == 1 && !item.link.is_empty()).then_some(item.link.as_str());
     }
+    item.links
+        .get(n.checked_sub(1)?)
+        .map(|(_, href)| href.as_str())
 }
 
 fn mark_feed_read(feed: &mut Feed) {
     for item in &mut feed.items {
-        item.read = true;
+        item.set_read(true);
     }
 }
 
@@ -163,71 +959,418 @@ fn mark_group_read(group: &mut Group) {
     group.update_unread();
 }
 
-fn open_unread_feed(feed: &mut Feed, opener: &str) {
+/// Mark items published before `cutoff` (a Unix timestamp) as read.
+fn mark_feed_read_before(feed: &mut Feed, cutoff: i64) {
     for item in &mut feed.items {
-        if !item.read {
-            open_link(opener, &item.link);
-            item.read = true;
+        if item.timestamp < cutoff {
+            item.set_read(true);
         }
     }
 }
 
-fn open_unread_group(group: &mut Group, opener: &str) {
+fn mark_group_read_before(group: &mut Group, cutoff: i64) {
+    for feed in &mut group.feeds {
+        mark_feed_read_before(feed, cutoff);
+    }
+    group.update_unread();
+}
+
+/// Parse an age such as `3d`, `12h`, `2w` or `45m` into seconds. A bare
+/// number is taken as days.
+fn parse_age(input: &str) -> Option<i64> {
+    let input = input.trim();
+    let (num, unit) = match input.find(|c: char| !c.is_ascii_digit()) {
+        Some(pos) => input.split_at(pos),
+        None => (input, "d"),
+    };
+    let n: i64 = num.parse().ok()?;
+    let secs = match unit.trim() {
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    n.checked_mul(secs)
+}
+
+fn open_unread_feed(feed: &mut Feed, opener: &Opener) {
+    let background = feed.open_in_background.unwrap_or(opener.background);
+    for item in &mut feed.items {
+        if item.is_unread() {
+            open_link(&opener.command, background, &item.link);
+            item.set_read(true);
+        }
+    }
+}
+
+fn open_unread_group(group: &mut Group, opener: &Opener) {
     for feed in &mut group.feeds {
         open_unread_feed(feed, opener);
     }
     group.update_unread();
 }
 
+/// Cycle a sort override: inherit → date → title → channel → inherit.
+fn next_sort_override(current: Option<SortOrder>) -> Option<SortOrder> {
+    match current {
+        None => Some(SortOrder::Date),
+        Some(SortOrder::Date) => Some(SortOrder::Title),
+        Some(SortOrder::Title) => Some(SortOrder::Channel),
+        Some(SortOrder::Channel) => None,
+    }
+}
+
 /// Compute visible item indices based on search and unread filters and sort order.
 fn visible_indices(app: &AppState) -> Vec<usize> {
     let groups = app.groups.lock().unwrap();
-    if groups.is_empty() {
-        return Vec::new();
+    groups
+        .get(app.selected_group)
+        .and_then(|g| {
+            g.feeds
+                .get(app.selected_feed)
+                .map(|f| feed_indices(app, app.config.ui.read_filter(), g, f))
+        })
+        .unwrap_or_default()
+}
+
+/// Whether every character of `query` appears in `text` in order, ignoring
+/// case. Plain substrings are the common case of this.
+fn fuzzy_match(query: &str, text: &str) -> bool {
+    let mut chars = text.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|q| chars.any(|c| c == q))
+}
+
+/// Indices of the feeds whose titles match the feed filter.
+fn feed_matches(query: &str, feeds: &[Feed]) -> Vec<usize> {
+    (0..feeds.len())
+        .filter(|&i| fuzzy_match(query, &feeds[i].title))
+        .collect()
+}
+
+/// Sort order for a feed's items: the feed's override, then the group's,
+/// then the global setting.
+fn effective_sort(app: &AppState, group: &Group, feed: &Feed) -> SortOrder {
+    feed.sort.or(group.sort).unwrap_or(app.config.ui.sort)
+}
+
+/// Indices of a feed's items for display, as [`filtered_indices`]. Feeds
+/// grouped by category list their items in sections by category name,
+/// uncategorized last; a collapsed section keeps only its first item, which
+/// is drawn as the section header. With `page_size` set only the first
+/// [`item_limit`] items are listed.
+fn feed_indices(app: &AppState, filter: ReadFilter, group: &Group, feed: &Feed) -> Vec<usize> {
+    let mut idx = unpaged_indices(app, filter, group, feed);
+    if let Some(limit) = item_limit(app, feed) {
+        idx.truncate(limit);
     }
-    let g = app.selected_group;
-    if groups[g].feeds.is_empty() {
-        return Vec::new();
+    idx
+}
+
+/// Number of items listed for `feed` when `page_size` is set.
+fn item_limit(app: &AppState, feed: &Feed) -> Option<usize> {
+    let page = app.config.ui.page_size.filter(|&n| n > 0)?;
+    Some(app.visible_limits.get(&feed.url).copied().unwrap_or(page))
+}
+
+/// [`feed_indices`] before cutting the list to the feed's item limit.
+fn unpaged_indices(app: &AppState, filter: ReadFilter, group: &Group, feed: &Feed) -> Vec<usize> {
+    let mut idx = filtered_indices(app, filter, group, feed);
+    if feed.group_by_category {
+        // Stable, so each section keeps the feed's sort order.
+        idx.sort_by_key(|&i| {
+            let category = item_category(&feed.items[i]);
+            (category.is_none(), category)
+        });
+        let mut previous = None;
+        idx.retain(|&i| {
+            let category = item_category(&feed.items[i]);
+            let first = previous != Some(category);
+            previous = Some(category);
+            first
+                || !app
+                    .collapsed_categories
+                    .contains(category.unwrap_or_default())
+        });
     }
-    let f = app.selected_feed;
-    let items = &groups[g].feeds[f].items;
+    if let Some(threshold) = app.config.ui.collapse_similar_titles {
+        idx = similar_clusters(feed, &idx, threshold)
+            .into_iter()
+            .flat_map(|(rep, variants)| {
+                let expanded = app.expanded_variants.contains(&feed.items[rep].id);
+                std::iter::once(rep).chain(variants.into_iter().filter(move |_| expanded))
+            })
+            .collect();
+    }
+    idx
+}
+
+/// Number of earlier clusters an item is compared against. Re-posts tend to
+/// be close together in time, and this keeps long feeds cheap to draw.
+const SIMILAR_WINDOW: usize = 20;
+
+/// Group the items in `idx` whose titles are at least `threshold` similar.
+/// Each cluster is led by its newest item and listed where that item is in
+/// `idx`, followed by the older variants, newest first.
+fn similar_clusters(feed: &Feed, idx: &[usize], threshold: f64) -> Vec<(usize, Vec<usize>)> {
+    let mut by_date = idx.to_vec();
+    by_date.sort_by_key(|&i| std::cmp::Reverse(feed.items[i].timestamp));
+    let mut clusters: Vec<(usize, Vec<usize>)> = Vec::new();
+    let mut titles: Vec<Vec<char>> = Vec::new();
+    for i in by_date {
+        let title: Vec<char> = feed.items[i].title.to_lowercase().chars().collect();
+        let start = clusters.len().saturating_sub(SIMILAR_WINDOW);
+        match (start..clusters.len()).find(|&c| jaro_winkler(&titles[c], &title) >= threshold) {
+            Some(c) => clusters[c].1.push(i),
+            None => {
+                clusters.push((i, Vec::new()));
+                titles.push(title);
+            }
+        }
+    }
+    let position: HashMap<usize, usize> = idx.iter().enumerate().map(|(p, &i)| (i, p)).collect();
+    clusters.sort_by_key(|(rep, _)| position[rep]);
+    clusters
+}
+
+/// Jaro-Winkler similarity of two strings, from 0.0 (nothing in common) to
+/// 1.0 (identical).
+fn jaro_winkler(a: &[char], b: &[char]) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return if a == b { 1.0 } else { 0.0 };
+    }
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut b_used = vec![false; b.len()];
+    let mut a_matches = Vec::new();
+    for (i, ca) in a.iter().enumerate() {
+        let lo = i.saturating_sub(window);
+        let hi = (i + window + 1).min(b.len());
+        if let Some(j) = (lo..hi).find(|&j| !b_used[j] && b[j] == *ca) {
+            b_used[j] = true;
+            a_matches.push(*ca);
+        }
+    }
+    if a_matches.is_empty() {
+        return 0.0;
+    }
+    let b_matches = b
+        .iter()
+        .zip(&b_used)
+        .filter(|(_, used)| **used)
+        .map(|(c, _)| *c);
+    let transpositions = a_matches
+        .iter()
+        .zip(b_matches)
+        .filter(|(x, y)| **x != *y)
+        .count()
+        / 2;
+    let m = a_matches.len() as f64;
+    let jaro = (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0;
+    let prefix = a.iter().zip(b).take(4).take_while(|(x, y)| x == y).count();
+    jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+/// Category an item is listed under when its feed is grouped by category.
+fn item_category(item: &Item) -> Option<&str> {
+    item.categories.first().map(String::as_str)
+}
+
+/// Filter and sort a feed's items, returning indices into `feed.items`.
+/// Items read longer ago than `hide_read_after_days` are left out.
+/// The sort order is resolved feed, then group, then global.
+fn filtered_indices(app: &AppState, filter: ReadFilter, group: &Group, feed: &Feed) -> Vec<usize> {
+    let items = &feed.items;
     let query = app.search.to_lowercase();
+    let hide_before = app
+        .config
+        .ui
+        .hide_read_after_days
+        .map(|days| Utc::now().timestamp() - i64::from(days) * 24 * 60 * 60);
     let mut idx: Vec<usize> = items
         .iter()
         .enumerate()
         .filter(|(_, i)| {
-            (!app.config.ui.unread_only || !i.read)
+            filter.matches(i.read)
+                && (app.show_hidden || !i.hidden)
+                && !(i.read && hide_before.is_some_and(|t| i.read_at.unwrap_or(i.timestamp) < t))
                 && (query.is_empty() || i.title.to_lowercase().contains(&query))
+                && app
+                    .category_filter
+                    .as_ref()
+                    .is_none_or(|c| i.categories.contains(c))
         })
         .map(|(i, _)| i)
         .collect();
-    match app.config.ui.sort {
-        crate::config::SortOrder::Date => {
-            idx.sort_by(|&a, &b| items[b].timestamp.cmp(&items[a].timestamp))
-        }
-        crate::config::SortOrder::Title => {
-            idx.sort_by(|&a, &b| items[a].title.cmp(&items[b].title))
+    let reverse = app.config.ui.sort_reverse;
+    match effective_sort(app, group, feed) {
+        SortOrder::Date => idx.sort_by(|&a, &b| {
+            let (a, b) = (&items[a], &items[b]);
+            // Reversed, dated items go oldest first; undated ones stay last.
+            if reverse && a.timestamp != 0 && b.timestamp != 0 {
+                data::newest_first(b, a)
+            } else {
+                data::newest_first(a, b)
+            }
+        }),
+        SortOrder::Title => {
+            idx.sort_by(|&a, &b| items[a].title.cmp(&items[b].title));
+            if reverse {
+                idx.reverse();
+            }
         }
-        crate::config::SortOrder::Channel => {
+        SortOrder::Channel => {
             // items belong to same feed; keep original order
+            if reverse {
+                idx.reverse();
+            }
         }
     }
     idx
 }
 
+/// Move the selection to the next (or previous) unread item, crossing feed
+/// and group boundaries in display order and wrapping at the ends.
+fn jump_unread(app: &mut AppState, forward: bool) {
+    let positions: Vec<(usize, usize, usize)> = {
+        let app: &AppState = app;
+        let groups = app.groups.lock().unwrap();
+        groups
+            .iter()
+            .enumerate()
+            .flat_map(|(g, group)| {
+                group.feeds.iter().enumerate().flat_map(move |(f, feed)| {
+                    feed_indices(app, app.config.ui.read_filter(), group, feed)
+                        .into_iter()
+                        .enumerate()
+                        .filter(|&(_, i)| feed.items[i].is_unread())
+                        .map(move |(pos, _)| (g, f, pos))
+                })
+            })
+            .collect()
+    };
+    let current = (app.selected_group, app.selected_feed, app.selected_item);
+    let found = if forward {
+        positions.iter().find(|&&p| p > current)
+    } else {
+        positions.iter().rev().find(|&&p| p < current)
+    };
+    let target = match found {
+        Some(p) => Some(p),
+        None => {
+            let wrapped = if forward {
+                positions.first()
+            } else {
+                positions.last()
+            };
+            app.message = Some(match wrapped {
+                Some(_) => "wrapped around".into(),
+                None => "no unread items".into(),
+            });
+            wrapped
+        }
+    };
+    if let Some(&(g, f, pos)) = target {
+        app.selected_group = g;
+        app.selected_feed = f;
+        app.selected_item = pos;
+        app.visual_anchor = None;
+        app.focus = Pane::Items;
+    }
+}
+
+/// A row of the Groups pane tree view.
+#[derive(Clone, Copy, PartialEq)]
+enum TreeRow {
+    Group(usize),
+    Feed(usize, usize),
+}
+
+/// Rows of the tree view: every group header, followed by its feeds when
+/// the group is expanded.
+fn tree_rows(groups: &[Group]) -> Vec<TreeRow> {
+    let mut rows = Vec::new();
+    for (g, group) in groups.iter().enumerate() {
+        rows.push(TreeRow::Group(g));
+        if group.expanded {
+            rows.extend((0..group.feeds.len()).map(|f| TreeRow::Feed(g, f)));
+        }
+    }
+    rows
+}
+
+/// The tree row the cursor is on.
+fn tree_cursor(app: &AppState, groups: &[Group]) -> TreeRow {
+    let expanded = groups
+        .get(app.selected_group)
+        .is_some_and(|g| g.expanded && app.selected_feed < g.feeds.len());
+    if app.tree_on_feed && expanded {
+        TreeRow::Feed(app.selected_group, app.selected_feed)
+    } else {
+        TreeRow::Group(app.selected_group)
+    }
+}
+
+/// Position one step up or down from `pos` in a list of `len` rows. Stops
+/// at the ends unless `wrap_navigation` is set.
+fn list_step(app: &AppState, pos: usize, len: usize, down: bool) -> usize {
+    let wrap = app.config.ui.wrap_navigation;
+    match (down, wrap) {
+        (true, _) if pos + 1 < len => pos + 1,
+        (true, true) => 0,
+        (true, false) => pos,
+        (false, _) if pos > 0 => pos - 1,
+        (false, true) => len.saturating_sub(1),
+        (false, false) => pos,
+    }
+}
+
+/// The tree row one step up or down from the cursor, if any.
+fn tree_step(app: &AppState, groups: &[Group], down: bool) -> Option<TreeRow> {
+    let rows = tree_rows(groups);
+    let current = tree_cursor(app, groups);
+    let pos = rows.iter().position(|&r| r == current)?;
+    let next = list_step(app, pos, rows.len(), down);
+    (next != pos).then(|| rows[next])
+}
+
 fn handle_groups_key(code: KeyCode, app: &mut AppState) -> Result<(), Box<dyn std::error::Error>> {
     let mut groups = app.groups.lock().unwrap();
     match code {
-        KeyCode::Up => {
-            if app.selected_group > 0 {
-                app.selected_group -= 1;
-                app.selected_feed = 0;
-                app.selected_item = 0;
-            }
+        KeyCode::Up | KeyCode::Down if app.config.ui.tree_view => {
+            match tree_step(app, &groups, code == KeyCode::Down) {
+                Some(TreeRow::Group(g)) => {
+                    if g != app.selected_group {
+                        app.selected_feed = 0;
+                    }
+                    app.selected_group = g;
+                    app.tree_on_feed = false;
+                    app.selected_item = 0;
+                }
+                Some(TreeRow::Feed(g, f)) => {
+                    app.selected_group = g;
+                    app.selected_feed = f;
+                    app.tree_on_feed = true;
+                    app.selected_item = 0;
+                }
+                None => {}
+            }
+        }
+        KeyCode::Enter | KeyCode::Char(' ') if app.config.ui.tree_view => {
+            if let TreeRow::Feed(..) = tree_cursor(app, &groups) {
+                app.focus = Pane::Items;
+            } else if let Some(group) = groups.get_mut(app.selected_group) {
+                group.expanded = !group.expanded;
+                app.dirty = true;
+                app.tree_on_feed = false;
+            }
         }
-        KeyCode::Down => {
-            if app.selected_group + 1 < groups.len() {
-                app.selected_group += 1;
+        KeyCode::Up | KeyCode::Down => {
+            let next = list_step(app, app.selected_group, groups.len(), code == KeyCode::Down);
+            if next != app.selected_group {
+                app.selected_group = next;
                 app.selected_feed = 0;
                 app.selected_item = 0;
             }
@@ -235,6 +1378,10 @@ fn handle_groups_key(code: KeyCode, app: &mut AppState) -> Result<(), Box<dyn st
         KeyCode::Right => {
             app.focus = Pane::Feeds;
         }
+        KeyCode::Char('\'') => {
+            drop(groups);
+            start_type_ahead(app);
+        }
         KeyCode::Char('a') => {
             app.input_popup = Some(InputPopup {
                 title: "New Group".into(),
@@ -242,37 +1389,83 @@ fn handle_groups_key(code: KeyCode, app: &mut AppState) -> Result<(), Box<dyn st
                 submit: InputAction::AddGroup,
             });
         }
-        KeyCode::Char('d') => {
-            if !groups.is_empty() {
-                let name = groups[app.selected_group].name.clone();
-                if confirm(&format!("Delete group '{}' ?", name)) {
-                    groups.remove(app.selected_group);
-                    if app.selected_group >= groups.len() && app.selected_group > 0 {
-                        app.selected_group -= 1;
-                    }
-                    app.selected_feed = 0;
-                    app.selected_item = 0;
-                }
+        KeyCode::Char('y') => {
+            if let Some(group) = groups.get(app.selected_group) {
+                let opml = data::opml::to_opml(std::slice::from_ref(group));
+                app.message = Some(match clipboard::copy(&opml) {
+                    Ok(()) => format!(
+                        "copied OPML for '{}' ({} feeds)",
+                        group.name,
+                        group.feeds.len()
+                    ),
+                    Err(e) => format!("copy failed: {}", e),
+                });
             }
         }
+        KeyCode::Char('I') => {
+            app.input_popup = Some(InputPopup {
+                title: "Import OPML from".into(),
+                buffer: String::new(),
+                submit: InputAction::ImportOpml,
+            });
+        }
+        KeyCode::Char('X') => {
+            app.input_popup = Some(InputPopup {
+                title: "Export JSON backup to".into(),
+                buffer: "mrss-backup.json".into(),
+                submit: InputAction::ExportJson,
+            });
+        }
+        KeyCode::Char('J') => {
+            app.input_popup = Some(InputPopup {
+                title: "Import JSON backup from".into(),
+                buffer: String::new(),
+                submit: InputAction::ImportJson,
+            });
+        }
+        KeyCode::Char('d') if !groups.is_empty() => {
+            drop(groups);
+            request_bulk(app, BulkAction::DeleteGroup(app.selected_group));
+        }
         KeyCode::Char('r') => {
-            if let Some(group) = groups.get_mut(app.selected_group)
-                && let Some(name) = prompt("Rename group:")
-            {
-                group.name = name;
+            if let Some(group) = groups.get(app.selected_group) {
+                app.input_popup = Some(InputPopup {
+                    title: "Rename group".into(),
+                    buffer: group.name.clone(),
+                    submit: InputAction::RenameGroup(app.selected_group),
+                });
             }
         }
         KeyCode::Char('A') => {
+            drop(groups);
+            request_bulk(app, BulkAction::MarkGroupRead(app.selected_group));
+        }
+        KeyCode::Char('O') => {
+            drop(groups);
+            request_bulk(app, BulkAction::OpenGroupUnread(app.selected_group));
+        }
+        KeyCode::Char('U') => {
             if let Some(group) = groups.get_mut(app.selected_group) {
-                mark_group_read(group);
+                let added = queue_unread(&mut group.feeds, &mut app.queue);
+                app.message = Some(queued_message(added, app.queue.len()));
+                app.dirty = true;
             }
         }
-        KeyCode::Char('O') => {
-            if let Some(group) = groups.get_mut(app.selected_group)
-                && confirm("Open all unread items in group?")
-            {
-                let opener = app.config.opener.command.clone();
-                open_unread_group(group, &opener);
+        KeyCode::Char('b') if !groups.is_empty() => {
+            app.input_popup = Some(InputPopup {
+                title: "Mark group read older than (e.g. 3d, 12h)".into(),
+                buffer: String::new(),
+                submit: InputAction::MarkReadOlder {
+                    group: app.selected_group,
+                    feed: None,
+                },
+            });
+        }
+        KeyCode::Char('s') => {
+            if let Some(group) = groups.get_mut(app.selected_group) {
+                group.sort = next_sort_override(group.sort);
+                app.dirty = true;
+                app.selected_item = 0;
             }
         }
         _ => {}
@@ -282,22 +1475,35 @@ fn handle_groups_key(code: KeyCode, app: &mut AppState) -> Result<(), Box<dyn st
 
 fn handle_feeds_key(code: KeyCode, app: &mut AppState) -> Result<(), Box<dyn std::error::Error>> {
     let mut groups = app.groups.lock().unwrap();
-    if groups.is_empty() {
+    // Adding is allowed without groups; it creates the target group.
+    if groups.is_empty() && code != KeyCode::Char('a') {
         return Ok(());
     }
     let g = app.selected_group;
+    let shown = groups
+        .get(g)
+        .map(|group| feed_matches(&app.feed_search, &group.feeds))
+        .unwrap_or_default();
+    let pos = shown.iter().position(|&i| i == app.selected_feed);
     match code {
-        KeyCode::Up => {
-            if app.selected_feed > 0 {
-                app.selected_feed -= 1;
+        KeyCode::Up | KeyCode::Down if !shown.is_empty() => {
+            let next = match pos {
+                None => 0,
+                Some(p) => list_step(app, p, shown.len(), code == KeyCode::Down),
+            };
+            if Some(next) != pos {
+                app.selected_feed = shown[next];
                 app.selected_item = 0;
             }
         }
-        KeyCode::Down => {
-            if app.selected_feed + 1 < groups[g].feeds.len() {
-                app.selected_feed += 1;
-                app.selected_item = 0;
-            }
+        KeyCode::Char('/') => {
+            app.input_popup = Some(InputPopup {
+                title: "Filter feeds".into(),
+                buffer: app.feed_search.clone(),
+                submit: InputAction::FeedSearch {
+                    previous: app.feed_search.clone(),
+                },
+            });
         }
         KeyCode::Left => {
             app.focus = Pane::Groups;
@@ -305,42 +1511,196 @@ fn handle_feeds_key(code: KeyCode, app: &mut AppState) -> Result<(), Box<dyn std
         KeyCode::Right => {
             app.focus = Pane::Items;
         }
+        KeyCode::Char('\'') => {
+            drop(groups);
+            start_type_ahead(app);
+        }
         KeyCode::Char('a') => {
-            if let Some(url) = prompt("Feed URL:") {
-                let feed = Feed {
-                    url: url.clone(),
-                    title: url,
-                    ..Feed::default()
-                };
-                groups[g].feeds.push(feed);
-                app.selected_feed = groups[g].feeds.len() - 1;
-                app.selected_item = 0;
-            }
+            app.input_popup = Some(InputPopup {
+                title: "Feed URL".into(),
+                buffer: String::new(),
+                submit: InputAction::AddFeed,
+            });
         }
-        KeyCode::Char('d') => {
-            if !groups[g].feeds.is_empty() {
-                let title = groups[g].feeds[app.selected_feed].title.clone();
-                if confirm(&format!("Delete feed '{}' ?", title)) {
-                    groups[g].feeds.remove(app.selected_feed);
-                    if app.selected_feed >= groups[g].feeds.len() && app.selected_feed > 0 {
-                        app.selected_feed -= 1;
-                    }
-                    groups[g].update_unread();
-                    app.selected_item = 0;
-                }
+        KeyCode::Char('+') => {
+            let group = app
+                .config
+                .ui
+                .default_group
+                .clone()
+                .or_else(|| groups.get(g).map(|group| group.name.clone()))
+                .unwrap_or_else(|| "Feeds".into());
+            app.input_popup = Some(InputPopup {
+                title: format!("Add feeds to '{}' (URLs separated by spaces)", group),
+                buffer: String::new(),
+                submit: InputAction::AddFeeds { group },
+            });
+        }
+        KeyCode::Char('E') => {
+            if let Some(feed) = groups[g].feeds.get(app.selected_feed) {
+                app.input_popup = Some(InputPopup {
+                    title: format!("URL of '{}'", feed.title),
+                    buffer: feed.url.clone(),
+                    submit: InputAction::EditFeedUrl {
+                        group: g,
+                        url: feed.url.clone(),
+                    },
+                });
             }
         }
+        KeyCode::Char('d') if !groups[g].feeds.is_empty() => {
+            drop(groups);
+            request_bulk(app, BulkAction::DeleteFeed(g, app.selected_feed));
+        }
         KeyCode::Char('A') => {
             if let Some(feed) = groups[g].feeds.get_mut(app.selected_feed) {
                 mark_feed_read(feed);
                 groups[g].update_unread();
+                app.dirty = true;
             }
         }
         KeyCode::Char('O') => {
+            drop(groups);
+            request_bulk(app, BulkAction::OpenFeedUnread(g, app.selected_feed));
+        }
+        KeyCode::Char('U') => {
+            let f = app.selected_feed;
+            if let Some(feeds) = groups[g].feeds.get_mut(f..=f) {
+                let added = queue_unread(feeds, &mut app.queue);
+                app.message = Some(queued_message(added, app.queue.len()));
+                app.dirty = true;
+            }
+        }
+        KeyCode::Char('b') if !groups[g].feeds.is_empty() => {
+            app.input_popup = Some(InputPopup {
+                title: "Mark feed read older than (e.g. 3d, 12h)".into(),
+                buffer: String::new(),
+                submit: InputAction::MarkReadOlder {
+                    group: g,
+                    feed: Some(app.selected_feed),
+                },
+            });
+        }
+        KeyCode::Char('p') => {
             if let Some(feed) = groups[g].feeds.get_mut(app.selected_feed) {
-                let opener = app.config.opener.command.clone();
-                open_unread_feed(feed, &opener);
-                groups[g].update_unread();
+                feed.pinned = !feed.pinned;
+                app.dirty = true;
+                let url = feed.url.clone();
+                groups[g].sort_pinned();
+                if let Some(pos) = groups[g].feeds.iter().position(|f| f.url == url) {
+                    app.selected_feed = pos;
+                }
+            }
+        }
+        KeyCode::Char('B') => {
+            if let Some(feed) = groups[g].feeds.get_mut(app.selected_feed) {
+                let background = !feed
+                    .open_in_background
+                    .unwrap_or(app.config.opener.background);
+                feed.open_in_background = Some(background);
+                app.dirty = true;
+                app.message = Some(format!(
+                    "'{}' opens links {}",
+                    feed.title,
+                    if background {
+                        "in the background"
+                    } else {
+                        "in the foreground"
+                    }
+                ));
+            }
+        }
+        KeyCode::Char('D') => {
+            if let Some(feed) = groups[g].feeds.get_mut(app.selected_feed) {
+                feed.unsubscribed = !feed.unsubscribed;
+                app.dirty = true;
+                app.message = Some(if feed.unsubscribed {
+                    format!(
+                        "unsubscribed from '{}'; items kept (D resubscribes)",
+                        feed.title
+                    )
+                } else {
+                    format!("resubscribed to '{}'", feed.title)
+                });
+            }
+        }
+        KeyCode::Char('y') => {
+            if let Some(feed) = groups[g].feeds.get(app.selected_feed) {
+                app.message = Some(match clipboard::copy(&feed.url) {
+                    Ok(()) => format!("copied: {}", feed.url),
+                    Err(e) => format!("copy failed: {}", e),
+                });
+            }
+        }
+        KeyCode::Char('C') => {
+            if let Some(feed) = groups[g].feeds.get_mut(app.selected_feed) {
+                let full = !feed
+                    .prefer_full_content
+                    .unwrap_or(app.config.ui.prefer_full_content);
+                feed.prefer_full_content = Some(full);
+                app.dirty = true;
+                app.message = Some(format!(
+                    "'{}' previews {}",
+                    feed.title,
+                    if full { "full content" } else { "summaries" }
+                ));
+            }
+        }
+        KeyCode::Char('H') => {
+            if let Some(feed) = groups[g].feeds.get(app.selected_feed) {
+                match &feed.home_url {
+                    Some(url) => {
+                        let background = feed
+                            .open_in_background
+                            .unwrap_or(app.config.opener.background);
+                        open_link(&app.config.opener.command, background, url);
+                    }
+                    None => app.message = Some(format!("'{}' has no homepage link", feed.title)),
+                }
+            }
+        }
+        KeyCode::Char('R') => {
+            if let Some(feed) = groups[g].feeds.get(app.selected_feed) {
+                app.retrying = Some(Retry {
+                    group: g,
+                    url: feed.url.clone(),
+                    last_fetch: feed.last_fetch,
+                    error_streak: feed.error_streak,
+                });
+                net::refresh::spawn_feed_refresh(
+                    Arc::clone(&app.groups),
+                    g,
+                    feed.url.clone(),
+                    app.config.refresh.max_failures,
+                    app.config.refresh.lenient_parsing,
+                    app.config.refresh.skip_linkless_entries,
+                );
+                app.message = Some(format!("retrying '{}'…", feed.title));
+            }
+        }
+        KeyCode::Char('e') => {
+            if let Some(feed) = groups[g].feeds.get_mut(app.selected_feed)
+                && feed.disabled
+            {
+                feed.disabled = false;
+                feed.error_streak = 0;
+                app.dirty = true;
+                net::refresh::spawn_feed_refresh(
+                    Arc::clone(&app.groups),
+                    g,
+                    feed.url.clone(),
+                    app.config.refresh.max_failures,
+                    app.config.refresh.lenient_parsing,
+                    app.config.refresh.skip_linkless_entries,
+                );
+                app.message = Some(format!("re-enabled '{}'", feed.title));
+            }
+        }
+        KeyCode::Char('s') => {
+            if let Some(feed) = groups[g].feeds.get_mut(app.selected_feed) {
+                feed.sort = next_sort_override(feed.sort);
+                app.dirty = true;
+                app.selected_item = 0;
             }
         }
         _ => {}
@@ -349,6 +1709,48 @@ fn handle_feeds_key(code: KeyCode, app: &mut AppState) -> Result<(), Box<dyn std
 }
 
 fn handle_items_key(code: KeyCode, app: &mut AppState) -> Result<(), Box<dyn std::error::Error>> {
+    let before = visible_ids(app);
+    items_action(code, app)?;
+    let after = visible_ids(app);
+    if after != before {
+        app.selected_item = reselect(&before, app.selected_item, &after);
+    }
+    Ok(())
+}
+
+/// Ids of the listed items, in display order.
+fn visible_ids(app: &AppState) -> Vec<String> {
+    let indices = visible_indices(app);
+    let groups = app.groups.lock().unwrap();
+    let Some(feed) = groups
+        .get(app.selected_group)
+        .and_then(|g| g.feeds.get(app.selected_feed))
+    else {
+        return Vec::new();
+    };
+    indices
+        .iter()
+        .filter_map(|&i| feed.items.get(i))
+        .map(|item| item.id.clone())
+        .collect()
+}
+
+/// Position in `after` for the item at `selected` in `before`: the same item
+/// if it's still listed, else the nearest following one that is, else the
+/// nearest preceding one. Keeps the cursor in place when marking items read
+/// removes them from a filtered list.
+fn reselect(before: &[String], selected: usize, after: &[String]) -> usize {
+    let selected = selected.min(before.len().saturating_sub(1));
+    before
+        .get(selected..)
+        .into_iter()
+        .flatten()
+        .chain(before[..selected.min(before.len())].iter().rev())
+        .find_map(|id| after.iter().position(|a| a == id))
+        .unwrap_or(0)
+}
+
+fn items_action(code: KeyCode, app: &mut AppState) -> Result<(), Box<dyn std::error::Error>> {
     let indices = visible_indices(app);
     if indices.is_empty() {
         return Ok(());
@@ -361,261 +1763,1247 @@ fn handle_items_key(code: KeyCode, app: &mut AppState) -> Result<(), Box<dyn std
         app.selected_item = items_len.saturating_sub(1);
     }
     match code {
-        KeyCode::Up => {
-            if app.selected_item > 0 {
-                app.selected_item -= 1;
-            }
-        }
-        KeyCode::Down => {
-            if app.selected_item + 1 < items_len {
-                app.selected_item += 1;
-            }
+        KeyCode::Up | KeyCode::Down => {
+            app.selected_item = list_step(app, app.selected_item, items_len, code == KeyCode::Down);
         }
         KeyCode::Left => {
+            app.visual_anchor = None;
             app.focus = Pane::Feeds;
         }
+        KeyCode::Char('V') => {
+            app.visual_anchor = match app.visual_anchor {
+                Some(_) => None,
+                None => Some(app.selected_item),
+            };
+        }
+        KeyCode::Esc => {
+            app.visual_anchor = None;
+        }
+        KeyCode::Char('m') if app.visual_anchor.is_some() => {
+            if let Some(range) = app.visual_range(items_len) {
+                for &idx in &indices[range] {
+                    groups[g].feeds[f].items[idx].set_read(true);
+                }
+                groups[g].update_unread();
+            }
+            app.dirty = true;
+            app.visual_anchor = None;
+        }
+        KeyCode::Char('q') if app.visual_anchor.is_some() => {
+            if let Some(range) = app.visual_range(items_len) {
+                for &idx in &indices[range] {
+                    enqueue(&mut app.queue, &mut groups[g].feeds[f].items[idx]);
+                }
+            }
+            app.dirty = true;
+            app.visual_anchor = None;
+        }
         KeyCode::Enter => {
-            let opener = app.config.opener.command.clone();
             let idx = indices[app.selected_item];
-            let item = &groups[g].feeds[f].items[idx];
-            open_link(&opener, &item.link);
+            let feed = &groups[g].feeds[f];
+            let item = &feed.items[idx];
+            app.reader = open_or_read(&app.config.opener, feed, &item.title, &item.link);
         }
         KeyCode::Char(' ') => {
             let idx = indices[app.selected_item];
             let item = &mut groups[g].feeds[f].items[idx];
-            item.read = !item.read;
+            item.set_read(!item.read);
             groups[g].update_unread();
+            app.dirty = true;
         }
         KeyCode::Char('m') => {
             let idx = indices[app.selected_item];
             let item = &mut groups[g].feeds[f].items[idx];
-            item.read = true;
+            item.set_read(true);
             groups[g].update_unread();
+            app.dirty = true;
         }
         KeyCode::Char('M') => {
             let idx = indices[app.selected_item];
             let item = &mut groups[g].feeds[f].items[idx];
-            item.read = false;
+            item.set_read(false);
             groups[g].update_unread();
+            app.dirty = true;
         }
         KeyCode::Char('q') => {
             let idx = indices[app.selected_item];
             let item = &mut groups[g].feeds[f].items[idx];
-            item.queued = !item.queued;
+            item.set_queued(!item.queued);
             if item.queued {
                 app.queue.push(item.clone());
             } else {
                 app.queue.retain(|i| i.id != item.id);
             }
+            app.dirty = true;
+        }
+        KeyCode::Char('*') => {
+            let idx = indices[app.selected_item];
+            let item = &mut groups[g].feeds[f].items[idx];
+            item.set_starred(!item.starred);
+            app.dirty = true;
+        }
+        KeyCode::Char('x') => {
+            let idx = indices[app.selected_item];
+            let item = &mut groups[g].feeds[f].items[idx];
+            item.hidden = !item.hidden;
+            groups[g].update_unread();
+            app.dirty = true;
+        }
+        KeyCode::Char('X') => {
+            app.show_hidden = !app.show_hidden;
+            app.message = Some(if app.show_hidden {
+                "showing hidden items (x restores)".into()
+            } else {
+                "hidden items not shown".into()
+            });
+        }
+        KeyCode::Char('y') => {
+            let feed = &groups[g].feeds[f];
+            let text = citation(
+                app.config.ui.citation_template(),
+                feed,
+                &feed.items[indices[app.selected_item]],
+            );
+            app.message = Some(match clipboard::copy(&text) {
+                Ok(()) => format!("copied: {}", text),
+                Err(e) => format!("copy failed: {}", e),
+            });
+        }
+        KeyCode::Char('Y') => {
+            let ui = &mut app.config.ui;
+            ui.citation_style = ui.citation_style.next(ui.citation_template.is_some());
+            app.message = Some(format!("copy style: {}", ui.citation_style.label()));
         }
         KeyCode::Delete => {
             let idx = indices[app.selected_item];
             let item = &mut groups[g].feeds[f].items[idx];
             if item.queued {
-                item.queued = false;
+                item.set_queued(false);
                 app.queue.retain(|i| i.id != item.id);
+                app.dirty = true;
+            }
+        }
+        KeyCode::Char('v') => {
+            app.preview_mode = app.preview_mode.next();
+        }
+        KeyCode::Char('F') => {
+            let id = &groups[g].feeds[f].items[indices[app.selected_item]].id;
+            app.preview_full = match &app.preview_full {
+                Some(full) if full == id => None,
+                _ => Some(id.clone()),
+            };
+        }
+        KeyCode::Char('w') => {
+            let item = &groups[g].feeds[f].items[indices[app.selected_item]];
+            app.input_popup = Some(InputPopup {
+                title: "Export item to (.md or .html)".into(),
+                buffer: format!("{}.md", file_stem(&item.title)),
+                submit: InputAction::ExportItem {
+                    group: g,
+                    feed: f,
+                    id: item.id.clone(),
+                },
+            });
+        }
+        KeyCode::Char('e') if app.config.ui.collapse_similar_titles.is_some() => {
+            let feed = &groups[g].feeds[f];
+            let selected = indices[app.selected_item];
+            let all = filtered_indices(app, app.config.ui.read_filter(), &groups[g], feed);
+            let threshold = app.config.ui.collapse_similar_titles.unwrap_or_default();
+            if let Some((rep, variants)) = similar_clusters(feed, &all, threshold)
+                .into_iter()
+                .find(|(rep, variants)| *rep == selected || variants.contains(&selected))
+                && !variants.is_empty()
+            {
+                let id = feed.items[rep].id.clone();
+                app.selected_item = indices.iter().position(|&i| i == rep).unwrap_or_default();
+                if !app.expanded_variants.remove(&id) {
+                    app.expanded_variants.insert(id);
+                }
+            }
+        }
+        KeyCode::Char('L') => {
+            let feed = &groups[g].feeds[f];
+            if let Some(limit) = item_limit(app, feed) {
+                let page = app.config.ui.page_size.unwrap_or_default();
+                app.visible_limits.insert(feed.url.clone(), limit + page);
+            }
+        }
+        KeyCode::Char('G') => {
+            let feed = &mut groups[g].feeds[f];
+            feed.group_by_category = !feed.group_by_category;
+            app.dirty = true;
+            app.message = Some(if feed.group_by_category {
+                "grouped by category (z folds a section)".into()
+            } else {
+                "category grouping off".into()
+            });
+        }
+        KeyCode::Char('z') if groups[g].feeds[f].group_by_category => {
+            let items = &groups[g].feeds[f].items;
+            let category = |pos: usize| item_category(&items[indices[pos]]);
+            let current = category(app.selected_item);
+            // Keep the cursor on the section, whose first item stays listed.
+            app.selected_item = (0..app.selected_item)
+                .rev()
+                .take_while(|&pos| category(pos) == current)
+                .last()
+                .unwrap_or(app.selected_item);
+            let key = current.unwrap_or_default().to_string();
+            if !app.collapsed_categories.remove(&key) {
+                app.collapsed_categories.insert(key);
             }
         }
-        KeyCode::Char('Q') => {
-            app.focus = Pane::Queue;
+        KeyCode::Char('c') => {
+            let mut categories: Vec<&String> = groups[g].feeds[f]
+                .items
+                .iter()
+                .flat_map(|i| &i.categories)
+                .collect();
+            categories.sort();
+            categories.dedup();
+            let next = match &app.category_filter {
+                None => categories.first(),
+                Some(cur) => categories
+                    .iter()
+                    .position(|c| *c == cur)
+                    .and_then(|p| categories.get(p + 1)),
+            };
+            app.category_filter = next.map(|c| (*c).clone());
+            app.selected_item = 0;
+        }
+        KeyCode::Char(c @ '1'..='9') => {
+            let idx = indices[app.selected_item];
+            let feed = &groups[g].feeds[f];
+            let item = &feed.items[idx];
+            if let Some(link) = item_link(item, c as usize - '0' as usize) {
+                app.reader = open_or_read(&app.config.opener, feed, &item.title, link);
+            }
         }
         _ => {}
     }
     Ok(())
 }
 
-fn handle_queue_key(code: KeyCode, app: &mut AppState) -> Result<(), Box<dyn std::error::Error>> {
+fn handle_preview_key(code: KeyCode, app: &mut AppState) -> Result<(), Box<dyn std::error::Error>> {
     match code {
-        KeyCode::Esc | KeyCode::Char('q') => {
+        KeyCode::Char('1'..='9' | 'v' | 'w' | 'F' | 'y' | 'Y') => handle_items_key(code, app)?,
+        KeyCode::Left => {
             app.focus = Pane::Items;
         }
-        KeyCode::Enter => {
-            let opener = app.config.opener.command.clone();
-            let ids: Vec<String> = app.queue.iter().map(|i| i.id.clone()).collect();
-            let mut groups = app.groups.lock().unwrap();
-            for id in ids {
-                for group in groups.iter_mut() {
-                    for feed in &mut group.feeds {
-                        if let Some(item) = feed.items.iter_mut().find(|it| it.id == id) {
-                            open_link(&opener, &item.link);
-                            item.read = true;
-                            item.queued = false;
-                        }
-                    }
-                    group.update_unread();
-                }
+        KeyCode::Char('f') => {
+            if selected_item(app).is_some_and(|item| !item.links.is_empty()) {
+                app.link_hints = Some(String::new());
+            } else {
+                app.message = Some("no links in this item".into());
             }
-            app.queue.clear();
-            app.focus = Pane::Items;
         }
         _ => {}
     }
     Ok(())
 }
 
-/// Run the application event loop.
-pub fn run_app(app: &mut AppState) -> Result<(), Box<dyn std::error::Error>> {
-    enable_raw_mode()?;
-    let mut stdout = std::io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+/// The item under the cursor in the Items pane, if any.
+fn selected_item(app: &AppState) -> Option<Item> {
+    let indices = visible_indices(app);
+    let idx = *indices.get(app.selected_item)?;
+    let groups = app.groups.lock().unwrap();
+    groups
+        .get(app.selected_group)?
+        .feeds
+        .get(app.selected_feed)?
+        .items
+        .get(idx)
+        .cloned()
+}
 
-    let tick_rate = Duration::from_millis(250);
-    let mut last_tick = Instant::now();
-    let mut last_key_time = Instant::now();
+/// Hint labels for `count` links: `a` to `z`, then `aa`, `ab`… once one
+/// letter isn't enough. All labels have the same length so none is a prefix
+/// of another.
+fn hint_labels(count: usize) -> Vec<String> {
+    let mut len = 1;
+    while 26usize.pow(len) < count {
+        len += 1;
+    }
+    (0..count)
+        .map(|mut n| {
+            let mut label = vec![b'a'; len as usize];
+            for slot in label.iter_mut().rev() {
+                *slot = b'a' + (n % 26) as u8;
+                n /= 26;
+            }
+            String::from_utf8(label).unwrap_or_default()
+        })
+        .collect()
+}
 
-    loop {
-        if let Ok((time, new)) = app.status_rx.try_recv() {
-            app.last_refresh = Some(time);
-            app.new_items = new;
+/// Keys in link hint mode: letters pick a link by its hint and open it,
+/// Backspace takes a letter back and Esc (or a letter matching no hint)
+/// leaves the mode.
+fn link_hint_key(code: KeyCode, app: &mut AppState) {
+    let Some(mut typed) = app.link_hints.take() else {
+        return;
+    };
+    match code {
+        KeyCode::Char(c) if c.is_ascii_lowercase() => typed.push(c),
+        KeyCode::Backspace => {
+            typed.pop();
+        }
+        _ => return,
+    }
+    let Some(item) = selected_item(app) else {
+        return;
+    };
+    let labels = hint_labels(item.links.len());
+    if let Some(n) = labels.iter().position(|l| *l == typed) {
+        let groups = app.groups.lock().unwrap();
+        if let Some(feed) = groups
+            .get(app.selected_group)
+            .and_then(|g| g.feeds.get(app.selected_feed))
+            && let Some(link) = item_link(&item, n + 1)
+        {
+            app.reader = open_or_read(&app.config.opener, feed, &item.title, link);
         }
-        terminal.draw(|f| ui(f, app))?;
+    } else if labels.iter().any(|l| l.starts_with(&typed)) {
+        app.link_hints = Some(typed);
+    } else {
+        app.message = Some(format!("no link hint '{}'", typed));
+    }
+}
 
-        let timeout = tick_rate
-            .checked_sub(last_tick.elapsed())
-            .unwrap_or_else(|| Duration::from_secs(0));
-        if event::poll(timeout)? {
-            let ev = event::read()?;
-            if let Event::Key(key) = ev {
-                if key.kind == KeyEventKind::Press {
-                    if last_key_time.elapsed() >= Duration::from_millis(100) {
-                        last_key_time = Instant::now();
-                        if let Some(popup) = app.input_popup.as_mut() {
-                            match key.code {
-                                KeyCode::Char(c) if key.modifiers.is_empty() => {
-                                    popup.buffer.push(c);
-                                }
-                                KeyCode::Backspace => {
-                                    popup.buffer.pop();
-                                }
-                                KeyCode::Enter => {
-                                    match popup.submit {
-                                        InputAction::AddGroup => {
-                                            let mut groups = app.groups.lock().unwrap();
-                                            let name = popup.buffer.trim().to_string();
-                                            if !name.is_empty() {
-                                                groups.push(Group {
-                                                    name: name.clone(),
-                                                    ..Group::default()
-                                                });
-                                                app.selected_group = groups.len() - 1;
-                                                app.selected_feed = 0;
-                                                app.selected_item = 0;
-                                            }
-                                        }
-                                    }
-                                    app.input_popup = None;
-                                }
-                                KeyCode::Esc => {
-                                    app.input_popup = None;
-                                }
-                                _ => {}
-                            }
-                        } else if key.code == KeyCode::Char('?') {
-                            app.show_help = !app.show_help;
-                        } else if key.code == KeyCode::Char('Q') {
-                            app.focus = Pane::Queue;
-                        } else if key.code == KeyCode::Char('q')
-                            && app.focus != Pane::Items
-                            && app.focus != Pane::Queue
-                        {
-                            let groups = app.groups.lock().unwrap();
-                            data::save_db(&groups)?;
-                            app.config.save()?;
-                            break;
-                        } else if key.code == KeyCode::Char('u') && key.modifiers.is_empty() {
-                            app.config.ui.unread_only = !app.config.ui.unread_only;
-                            app.selected_item = 0;
-                        } else if key.code == KeyCode::Tab {
-                            app.focus = match app.focus {
-                                Pane::Groups => Pane::Feeds,
-                                Pane::Feeds => Pane::Items,
-                                Pane::Items => Pane::Preview,
-                                Pane::Preview => Pane::Groups,
-                                Pane::Queue => Pane::Queue,
-                            };
-                        } else if key.code == KeyCode::BackTab {
-                            app.focus = match app.focus {
-                                Pane::Groups => Pane::Preview,
-                                Pane::Feeds => Pane::Groups,
-                                Pane::Items => Pane::Feeds,
-                                Pane::Preview => Pane::Items,
-                                Pane::Queue => Pane::Queue,
-                            };
-                        } else if key.code == KeyCode::Char('f')
-                            && key.modifiers.contains(KeyModifiers::CONTROL)
-                        {
-                            if let Some(q) = prompt("Search:") {
-                                app.search = q;
-                            } else {
-                                app.search.clear();
-                            }
-                            app.selected_item = 0;
-                        } else {
-                            match app.focus {
-                                Pane::Groups => handle_groups_key(key.code, app)?,
-                                Pane::Feeds => handle_feeds_key(key.code, app)?,
-                                Pane::Items => handle_items_key(key.code, app)?,
-                                Pane::Queue => handle_queue_key(key.code, app)?,
-                                _ => {}
-                            }
-                        }
-                    }
-                }
-            } else if let Event::Resize(_, _) = ev {
-                // just trigger a redraw on next loop
+/// Positions `(group, feed, item)` of every starred item, in display order.
+fn starred_items(groups: &[Group]) -> Vec<(usize, usize, usize)> {
+    groups
+        .iter()
+        .enumerate()
+        .flat_map(|(g, group)| {
+            group.feeds.iter().enumerate().flat_map(move |(f, feed)| {
+                feed.items
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, i)| i.starred)
+                    .map(move |(i, _)| (g, f, i))
+            })
+        })
+        .collect()
+}
+
+fn handle_queue_key(code: KeyCode, app: &mut AppState) -> Result<(), Box<dyn std::error::Error>> {
+    let len = if app.show_starred {
+        starred_items(&app.groups.lock().unwrap()).len()
+    } else {
+        app.queue.len()
+    };
+    app.queue_selected = app.queue_selected.min(len.saturating_sub(1));
+    match code {
+        KeyCode::Esc => {
+            app.focus = Pane::Items;
+            app.show_starred = false;
+        }
+        KeyCode::Left | KeyCode::Right => {
+            app.show_starred = !app.show_starred;
+            app.queue_selected = 0;
+        }
+        KeyCode::Up | KeyCode::Down => {
+            app.queue_selected = list_step(app, app.queue_selected, len, code == KeyCode::Down)
+        }
+        KeyCode::Char('s') if app.show_starred => {
+            // Starred → queue.
+            let mut groups = app.groups.lock().unwrap();
+            if let Some(&(g, f, i)) = starred_items(&groups).get(app.queue_selected) {
+                let item = &mut groups[g].feeds[f].items[i];
+                item.set_starred(false);
+                enqueue(&mut app.queue, item);
+                app.dirty = true;
             }
         }
-
-        if last_tick.elapsed() >= tick_rate {
-            last_tick = Instant::now();
+        KeyCode::Char('s') if app.queue_selected < app.queue.len() => {
+            // Queue → starred.
+            let id = app.queue.remove(app.queue_selected).id;
+            let mut groups = app.groups.lock().unwrap();
+            if let Some(item) = groups
+                .iter_mut()
+                .flat_map(|g| &mut g.feeds)
+                .flat_map(|f| &mut f.items)
+                .find(|i| i.id == id)
+            {
+                item.set_queued(false);
+                item.set_starred(true);
+                app.dirty = true;
+            }
         }
+        KeyCode::Enter if app.show_starred => {
+            let groups = app.groups.lock().unwrap();
+            if let Some(&(g, f, i)) = starred_items(&groups).get(app.queue_selected) {
+                let feed = &groups[g].feeds[f];
+                let item = &feed.items[i];
+                app.reader = open_or_read(&app.config.opener, feed, &item.title, &item.link);
+            }
+        }
+        KeyCode::Enter => request_bulk(app, BulkAction::OpenQueue),
+        _ => {}
     }
-
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
     Ok(())
 }
 
-/// Draw the main UI layout.
-fn ui(f: &mut Frame, app: &AppState) {
-    let outer = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Min(0),
-            Constraint::Length(1),
-            Constraint::Length(1),
-        ])
-        .split(f.size());
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage(20),
-            Constraint::Percentage(30),
-            Constraint::Percentage(50),
-        ])
-        .split(outer[0]);
+/// Route a key press to the open input popup.
+fn handle_popup_key(key: KeyEvent, app: &mut AppState) {
+    let Some(mut popup) = app.input_popup.take() else {
+        return;
+    };
+    match key.code {
+        KeyCode::Char(c)
+            if !key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+        {
+            popup.buffer.push(c);
+        }
+        KeyCode::Backspace => {
+            popup.buffer.pop();
+        }
+        KeyCode::Enter => {
+            submit_popup(popup, app);
+            return;
+        }
+        KeyCode::Esc => {
+            match popup.submit {
+                InputAction::Search { previous } => {
+                    app.search = previous;
+                    app.selected_item = 0;
+                }
+                InputAction::FeedSearch { previous } => app.set_feed_search(previous),
+                _ => {}
+            }
+            return;
+        }
+        _ => {}
+    }
+    match popup.submit {
+        InputAction::Search { .. } => {
+            app.search = popup.buffer.clone();
+            app.selected_item = 0;
+        }
+        InputAction::FeedSearch { .. } => app.set_feed_search(popup.buffer.clone()),
+        _ => {}
+    }
+    app.input_popup = Some(popup);
+}
 
-    let groups_guard = app.groups.lock().unwrap();
-    let group_items: Vec<ListItem> = groups_guard
-        .iter()
-        .map(|g| ListItem::new(g.name.clone()))
-        .collect();
-    let groups_list = List::new(group_items).block(
-        Block::default()
-            .title("Groups")
-            .border_style(if app.focus == Pane::Groups {
-                Style::default().fg(Color::Yellow)
+/// Apply a submitted input popup, marking the database dirty only when it
+/// actually changed.
+fn submit_popup(popup: InputPopup, app: &mut AppState) {
+    match popup.submit {
+        InputAction::AddGroup => {
+            let mut groups = app.groups.lock().unwrap();
+            let name = popup.buffer.trim().to_string();
+            if name.is_empty() {
+                return;
+            }
+            if let Some(existing) = data::find_group(&groups, &name, None) {
+                app.message = Some(format!("a group named '{}' already exists", name));
+                app.selected_group = existing;
+                app.selected_feed = 0;
+                app.selected_item = 0;
             } else {
-                Style::default()
+                groups.push(Group {
+                    name: name.clone(),
+                    ..Group::default()
+                });
+                app.selected_group = groups.len() - 1;
+                app.selected_feed = 0;
+                app.selected_item = 0;
+                app.dirty = true;
+            }
+        }
+        InputAction::RenameGroup(g) => {
+            let name = popup.buffer.trim();
+            if name.is_empty() {
+                return;
+            }
+            let mut groups = app.groups.lock().unwrap();
+            if data::find_group(&groups, name, Some(g)).is_some() {
+                app.message = Some(format!("a group named '{}' already exists", name));
+            } else if let Some(group) = groups.get_mut(g) {
+                group.name = name.to_string();
+                app.dirty = true;
+            }
+        }
+        InputAction::AddFeed => {
+            let url = net::normalize_url(popup.buffer.trim());
+            if url.is_empty() {
+                return;
+            }
+            if !url::Url::parse(&url).is_ok_and(|u| u.has_host()) {
+                app.message = Some(format!("invalid URL: {}", url));
+                return;
+            }
+            let groups = app.groups.lock().unwrap();
+            // Check the current group first so its name is reported.
+            let duplicate = groups
+                .get(app.selected_group)
+                .into_iter()
+                .chain(groups.iter())
+                .find(|group| {
+                    group
+                        .feeds
+                        .iter()
+                        .any(|feed| net::same_feed_url(&feed.url, &url))
+                })
+                .map(|group| group.name.clone());
+            let group = groups
+                .get(app.selected_group)
+                .map(|g| g.name.clone())
+                .or_else(|| app.config.ui.default_group.clone())
+                .unwrap_or_default();
+            app.input_popup = Some(InputPopup {
+                title: match duplicate {
+                    Some(name) => format!(
+                        "Already subscribed in '{}'; add anyway to group [Esc: cancel]",
+                        name
+                    ),
+                    None => "Add to group".into(),
+                },
+                buffer: group,
+                submit: InputAction::AddFeedToGroup { url },
+            });
+        }
+        InputAction::AddFeedToGroup { url } => {
+            let name = popup.buffer.trim();
+            if name.is_empty() {
+                return;
+            }
+            let mut groups = app.groups.lock().unwrap();
+            let target = data::find_or_create_group(&mut groups, name);
+            groups[target].feeds.push(Feed {
+                url: url.clone(),
+                title: url,
+                ..Feed::default()
+            });
+            app.selected_group = target;
+            app.selected_feed = groups[target].feeds.len() - 1;
+            app.selected_item = 0;
+            app.dirty = true;
+        }
+        InputAction::Search { .. } => {
+            app.search = popup.buffer;
+            app.selected_item = 0;
+        }
+        InputAction::FeedSearch { .. } => app.set_feed_search(popup.buffer),
+        InputAction::MarkReadOlder { group, feed } => {
+            let Some(age) = parse_age(&popup.buffer) else {
+                return;
+            };
+            let cutoff = Utc::now().timestamp() - age;
+            let mut groups = app.groups.lock().unwrap();
+            let Some(group) = groups.get_mut(group) else {
+                return;
+            };
+            match feed.and_then(|f| group.feeds.get_mut(f)) {
+                Some(feed) => {
+                    mark_feed_read_before(feed, cutoff);
+                    group.update_unread();
+                }
+                None if feed.is_none() => mark_group_read_before(group, cutoff),
+                None => return,
+            }
+            app.dirty = true;
+        }
+        InputAction::ImportOpml => {
+            let path = popup.buffer.trim();
+            if path.is_empty() {
+                return;
+            }
+            app.message = Some(match std::fs::read_to_string(path) {
+                Ok(text) => {
+                    let mut groups = app.groups.lock().unwrap();
+                    let subs = data::opml::parse_opml(&text);
+                    let summary = data::opml::merge_subscriptions(&mut groups, subs);
+                    app.dirty |= summary.added > 0;
+                    summary.to_string()
+                }
+                Err(e) => format!("import failed: {}", e),
+            });
+        }
+        InputAction::ExportJson => {
+            let path = popup.buffer.trim();
+            if path.is_empty() {
+                return;
+            }
+            let groups = app.groups.lock().unwrap();
+            app.message = Some(
+                match data::backup::export_json(&groups, std::path::Path::new(path)) {
+                    Ok(()) => format!("exported to {}", path),
+                    Err(e) => format!("export failed: {}", e),
+                },
+            );
+        }
+        InputAction::ImportJson => {
+            let path = popup.buffer.trim();
+            if path.is_empty() {
+                return;
+            }
+            let mut groups = app.groups.lock().unwrap();
+            app.message = Some(
+                match data::backup::import_json(&mut groups, std::path::Path::new(path)) {
+                    Ok(summary) => {
+                        app.dirty = true;
+                        summary.to_string()
+                    }
+                    Err(e) => format!("import failed: {}", e),
+                },
+            );
+        }
+        InputAction::AddFeeds { group } => {
+            let (valid, invalid): (Vec<_>, Vec<_>) = popup
+                .buffer
+                .split_whitespace()
+                .map(net::normalize_url)
+                .partition(|url| url::Url::parse(url).is_ok_and(|u| u.has_host()));
+            if valid.is_empty() && invalid.is_empty() {
+                return;
+            }
+            let mut groups = app.groups.lock().unwrap();
+            let subs = valid
+                .iter()
+                .map(|url| data::opml::Subscription {
+                    group: group.clone(),
+                    url: url.clone(),
+                    title: None,
+                })
+                .collect();
+            let summary = data::opml::merge_subscriptions(&mut groups, subs);
+            app.dirty |= summary.added > 0;
+            // Fetch the new feeds now so their titles and items show up
+            // without waiting for the next refresh.
+            if let Some(target) = data::find_group(&groups, &group, None) {
+                for feed in &groups[target].feeds {
+                    if feed.last_fetch.is_none() && valid.contains(&feed.url) {
+                        net::refresh::spawn_feed_refresh(
+                            Arc::clone(&app.groups),
+                            target,
+                            feed.url.clone(),
+                            app.config.refresh.max_failures,
+                            app.config.refresh.lenient_parsing,
+                            app.config.refresh.skip_linkless_entries,
+                        );
+                    }
+                }
+            }
+            app.message = Some(if invalid.is_empty() {
+                summary.to_string()
+            } else {
+                format!("{}, ignored invalid: {}", summary, invalid.join(" "))
+            });
+        }
+        InputAction::EditFeedUrl { group, url } => {
+            let new_url = net::normalize_url(popup.buffer.trim());
+            if popup.buffer.trim().is_empty() || new_url == url {
+                return;
+            }
+            if !url::Url::parse(&new_url).is_ok_and(|u| u.has_host()) {
+                app.message = Some(format!("invalid URL: {}", new_url));
+                return;
+            }
+            let mut groups = app.groups.lock().unwrap();
+            if let Some(other) = groups.iter().find(|g| {
+                g.feeds
+                    .iter()
+                    .any(|f| f.url != url && net::same_feed_url(&f.url, &new_url))
+            }) {
+                app.message = Some(format!("already subscribed in '{}'", other.name));
+                return;
+            }
+            let Some(feed) = groups
+                .get_mut(group)
+                .and_then(|g| g.feeds.iter_mut().find(|f| f.url == url))
+            else {
+                return;
+            };
+            feed.set_url(new_url.clone());
+            app.dirty = true;
+            app.message = Some(format!("'{}' now fetched from {}", feed.title, new_url));
+            net::refresh::spawn_feed_refresh(
+                Arc::clone(&app.groups),
+                group,
+                new_url,
+                app.config.refresh.max_failures,
+                app.config.refresh.lenient_parsing,
+                app.config.refresh.skip_linkless_entries,
+            );
+        }
+        InputAction::ExportItem { group, feed, id } => {
+            let path = popup.buffer.trim();
+            if path.is_empty() {
+                return;
+            }
+            let groups = app.groups.lock().unwrap();
+            let Some(feed) = groups.get(group).and_then(|g| g.feeds.get(feed)) else {
+                return;
+            };
+            let Some(item) = feed.items.iter().find(|i| i.id == id) else {
+                return;
+            };
+            let is_html = path.ends_with(".html") || path.ends_with(".htm");
+            let doc = if is_html {
+                item_html(feed, item)
+            } else {
+                item_markdown(feed, item)
+            };
+            app.message = Some(match std::fs::write(path, doc) {
+                Ok(()) => format!("exported to {}", path),
+                Err(e) => format!("export failed: {}", e),
+            });
+        }
+    }
+}
+
+/// Pause after which the next type-ahead letter starts a new name.
+const TYPE_AHEAD_RESET: Duration = Duration::from_secs(1);
+
+/// Start a type-ahead find in the focused pane.
+fn start_type_ahead(app: &mut AppState) {
+    app.type_ahead = Some(TypeAhead {
+        text: String::new(),
+        last_key: Instant::now(),
+    });
+    app.message = Some("find: ".into());
+}
+
+/// Feed a key to the active type-ahead find. Letters extend the name,
+/// Backspace shortens it and Esc/Enter end the find; any other key ends it and
+/// is handled as usual. Returns whether the key was used.
+fn type_ahead_key(key: KeyEvent, app: &mut AppState) -> bool {
+    let Some(find) = &mut app.type_ahead else {
+        return false;
+    };
+    match key.code {
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            if find.last_key.elapsed() >= TYPE_AHEAD_RESET {
+                find.text.clear();
+            }
+            find.text.push(c);
+        }
+        KeyCode::Backspace => {
+            find.text.pop();
+        }
+        KeyCode::Esc | KeyCode::Enter => {
+            app.type_ahead = None;
+            return true;
+        }
+        _ => {
+            app.type_ahead = None;
+            return false;
+        }
+    }
+    find.last_key = Instant::now();
+    let text = find.text.clone();
+    let found = text.is_empty() || jump_to_name(app, &text);
+    app.message = Some(if found {
+        format!("find: {}", text)
+    } else {
+        format!("find: {} (no match)", text)
+    });
+    true
+}
+
+/// Select the first group or feed, depending on the focused pane, whose name
+/// starts with `prefix`, ignoring case. Feeds hidden by the feed filter are
+/// skipped.
+fn jump_to_name(app: &mut AppState, prefix: &str) -> bool {
+    let prefix = prefix.to_lowercase();
+    let starts = |name: &str| name.to_lowercase().starts_with(&prefix);
+    let groups = app.groups.lock().unwrap();
+    match app.focus {
+        Pane::Groups => {
+            let Some(g) = groups.iter().position(|group| starts(&group.name)) else {
+                return false;
+            };
+            if g != app.selected_group {
+                app.selected_group = g;
+                app.selected_feed = 0;
+                app.selected_item = 0;
+            }
+            app.tree_on_feed = false;
+        }
+        Pane::Feeds => {
+            let Some(f) = groups.get(app.selected_group).and_then(|group| {
+                feed_matches(&app.feed_search, &group.feeds)
+                    .into_iter()
+                    .find(|&i| starts(&group.feeds[i].title))
+            }) else {
+                return false;
+            };
+            if f != app.selected_feed {
+                app.selected_feed = f;
+                app.selected_item = 0;
+            }
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Select a jump box result and focus its pane. Filters that would hide a
+/// chosen item are cleared, and the item list is extended to reach it.
+fn jump_to(app: &mut AppState, target: Target) {
+    let (group, feed) = match &target {
+        Target::Group(g) => (*g, None),
+        Target::Feed { group, feed } | Target::Item { group, feed, .. } => (*group, Some(*feed)),
+    };
+    app.selected_group = group;
+    app.selected_feed = feed.unwrap_or_default();
+    app.selected_item = 0;
+    app.tree_on_feed = feed.is_some();
+    app.focus = match target {
+        Target::Group(_) => Pane::Groups,
+        _ => Pane::Feeds,
+    };
+    if feed.is_some() {
+        app.feed_search.clear();
+        // Tree view only lists the feeds of expanded groups.
+        if app.config.ui.tree_view
+            && let Some(g) = app.groups.lock().unwrap().get_mut(group)
+        {
+            g.expanded = true;
+            app.dirty = true;
+        }
+    }
+    let Target::Item { id, .. } = target else {
+        return;
+    };
+    app.search.clear();
+    app.category_filter = None;
+    let position = |app: &AppState, paged: bool| {
+        let groups = app.groups.lock().unwrap();
+        let group = groups.get(app.selected_group)?;
+        let feed = group.feeds.get(app.selected_feed)?;
+        let filter = app.config.ui.read_filter();
+        let indices = if paged {
+            feed_indices(app, filter, group, feed)
+        } else {
+            unpaged_indices(app, filter, group, feed)
+        };
+        let pos = indices.iter().position(|&i| feed.items[i].id == id)?;
+        Some((pos, feed.url.clone()))
+    };
+    if position(app, false).is_none() {
+        app.config.ui.read_filter = Some(ReadFilter::All);
+    }
+    if let Some((pos, url)) = position(app, false)
+        && let Some(page) = app.config.ui.page_size.filter(|&n| n > 0)
+    {
+        let limit = app.visible_limits.entry(url).or_insert(page);
+        *limit = (*limit).max(pos + 1);
+    }
+    match position(app, true) {
+        Some((pos, _)) => {
+            app.selected_item = pos;
+            app.focus = Pane::Items;
+        }
+        None => app.message = Some("the item is in a folded section".into()),
+    }
+}
+
+/// Minimum time between automatic database saves; changes made in between
+/// are written together.
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Sync read state if configured, then save the database, config and
+/// session. A failed sync is reported after everything else is saved.
+fn save_state(app: &mut AppState) -> Result<(), Box<dyn std::error::Error>> {
+    app.leave_feed();
+    app.dirty = false;
+    let mut groups = app.groups.lock().unwrap();
+    let synced = match &app.config.sync.read_state_file {
+        Some(path) => data::sync::sync_read_state(&mut groups, path).map(|s| s.pulled > 0),
+        None => Ok(false),
+    };
+    // Flags pulled from other machines may have queued or dequeued items.
+    if synced.as_ref().is_ok_and(|&pulled| pulled) {
+        app.queue = queued_items(&groups);
+    }
+    data::save_db(&groups)?;
+    drop(groups);
+    app.config.save()?;
+    if app.config.ui.restore_session {
+        app.session().save()?;
+    }
+    synced.map(drop)
+}
+
+/// Write unread and refresh totals to `path` as JSON, e.g.
+/// `{"total_unread":42,"new_items":3,"last_refresh":"…","errors":1,...}`.
+/// `errors` counts feeds whose last fetch failed. The file is replaced in one
+/// step so readers never see it half written.
+fn write_status_file(app: &AppState, path: &Path) -> io::Result<()> {
+    let (total_unread, errors) = {
+        let groups = app.groups.lock().unwrap();
+        let errors = groups
+            .iter()
+            .flat_map(|g| &g.feeds)
+            .filter(|f| f.error_streak > 0)
+            .count();
+        (data::total_unread(&groups), errors)
+    };
+    let status = serde_json::json!({
+        "total_unread": total_unread,
+        "new_items": app.new_items,
+        "last_refresh": app.last_refresh.map(|t| t.to_rfc3339()),
+        "errors": errors,
+        "last_error": app.last_error.as_ref().map(|(url, error)| format!("{}: {}", url, error)),
+    });
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, status.to_string())?;
+    fs::rename(&tmp, path)
+}
+
+/// Put the terminal back into its normal mode, leaving the alternate screen
+/// if it was entered.
+fn restore_terminal(alt_screen: bool) -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), DisableBracketedPaste, Show)?;
+    if alt_screen {
+        execute!(io::stdout(), LeaveAlternateScreen)?;
+    }
+    Ok(())
+}
+
+/// Restore the terminal and save the database if the UI thread panics, so
+/// the panic message is readable and read state isn't lost. Panics on other
+/// threads are left to the default hook.
+fn install_panic_hook(groups: Arc<Mutex<Vec<Group>>>, alt_screen: bool) {
+    let ui_thread = std::thread::current().id();
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if std::thread::current().id() == ui_thread {
+            let _ = restore_terminal(alt_screen);
+            // The panicking code may hold the lock; skip saving rather than
+            // deadlock.
+            if let Ok(groups) = groups.try_lock() {
+                let _ = data::save_db(&groups);
+            }
+        }
+        previous(info);
+    }));
+}
+
+/// Run the application until the user quits or SIGINT arrives, then save
+/// and restore the terminal. State is saved even if the loop fails.
+pub fn run_app(app: &mut AppState) -> Result<(), Box<dyn std::error::Error>> {
+    let interrupted = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&interrupted))?;
+    install_panic_hook(Arc::clone(&app.groups), app.alt_screen);
+
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnableBracketedPaste)?;
+    if app.alt_screen {
+        execute!(io::stdout(), EnterAlternateScreen)?;
+    }
+    let result = Terminal::new(CrosstermBackend::new(io::stdout()))
+        .map_err(Into::into)
+        .and_then(|mut terminal| {
+            if !app.alt_screen {
+                // Drawing only touches changed cells; start from a blank
+                // screen so earlier output doesn't show through.
+                terminal.clear()?;
+            }
+            event_loop(&mut terminal, app, &interrupted)
+        });
+    let saved = save_state(app);
+    restore_terminal(app.alt_screen)?;
+    result.and(saved)
+}
+
+/// Route a key press: quit keys first, then an open popup or the help
+/// overlay, which capture everything else, then global keys and finally the
+/// focused pane. Returns `true` when the user asked to quit.
+fn handle_key(key: KeyEvent, app: &mut AppState) -> Result<bool, Box<dyn std::error::Error>> {
+    if type_ahead_key(key, app) {
+        return Ok(false);
+    }
+    if matches!(key.code, KeyCode::Char('q' | 'c')) && key.modifiers.contains(KeyModifiers::CONTROL)
+    {
+        return Ok(quit_now(app));
+    } else if app.link_hints.is_some() {
+        link_hint_key(key.code, app);
+    } else if app.input_popup.is_some() {
+        handle_popup_key(key, app);
+    } else if app.bulk_preview.is_some() {
+        if handle_bulk_preview_key(key.code, app) {
+            return Ok(true);
+        }
+    } else if let Some(jump) = &mut app.jump {
+        let outcome = jump.handle_key(key, &app.groups.lock().unwrap());
+        match outcome {
+            Outcome::Stay => {}
+            Outcome::Close => app.jump = None,
+            Outcome::Go(target) => {
+                app.jump = None;
+                jump_to(app, target);
+            }
+        }
+    } else if let Some(reader) = &mut app.reader {
+        let page = crossterm::terminal::size().map_or(10, |(_, h)| h.saturating_sub(3));
+        if !reader.handle_key(key.code, page) {
+            app.reader = None;
+        }
+    } else if app.show_help {
+        // The overlay covers the panes; keys only close it.
+        if matches!(key.code, KeyCode::Char('?') | KeyCode::Esc) {
+            app.show_help = false;
+        }
+    } else if key.code == KeyCode::Char('?') {
+        app.show_help = true;
+    } else if key.code == KeyCode::Char('Q') {
+        app.focus = Pane::Queue;
+    } else if matches!(key.code, KeyCode::Char('n' | 'N')) && app.focus != Pane::Queue {
+        jump_unread(app, key.code == KeyCode::Char('n'));
+    } else if key.code == KeyCode::Char('u') && key.modifiers.is_empty() {
+        app.config.ui.read_filter = Some(app.config.ui.read_filter().next());
+        app.selected_item = 0;
+    } else if key.code == KeyCode::Char('S') {
+        app.config.ui.sort_reverse = !app.config.ui.sort_reverse;
+        app.selected_item = 0;
+        app.message = Some(
+            if app.config.ui.sort_reverse {
+                "sort reversed"
+            } else {
+                "sort order restored"
+            }
+            .into(),
+        );
+    } else if key.code == KeyCode::Char('P') {
+        app.config.ui.show_preview = !app.config.ui.show_preview;
+        if app.focus == Pane::Preview && !app.preview_visible() {
+            app.focus = Pane::Items;
+        }
+    } else if key.code == KeyCode::Tab {
+        app.focus = match app.focus {
+            Pane::Groups => Pane::Feeds,
+            Pane::Feeds => Pane::Items,
+            Pane::Items if app.preview_visible() => Pane::Preview,
+            Pane::Items | Pane::Preview => Pane::Groups,
+            Pane::Queue => Pane::Queue,
+        };
+    } else if key.code == KeyCode::BackTab {
+        app.focus = match app.focus {
+            Pane::Groups if app.preview_visible() => Pane::Preview,
+            Pane::Groups => Pane::Items,
+            Pane::Feeds => Pane::Groups,
+            Pane::Items => Pane::Feeds,
+            Pane::Preview => Pane::Items,
+            Pane::Queue => Pane::Queue,
+        };
+    } else if key.code == KeyCode::Char('a') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        request_bulk(app, BulkAction::MarkAllRead);
+    } else if key.code == KeyCode::Char('g') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        app.jump = Some(Jump::default());
+    } else if key.code == KeyCode::Char('f') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        app.input_popup = Some(InputPopup {
+            title: "Search".into(),
+            buffer: app.search.clone(),
+            submit: InputAction::Search {
+                previous: app.search.clone(),
+            },
+        });
+    } else {
+        match app.focus {
+            Pane::Groups => handle_groups_key(key.code, app)?,
+            Pane::Feeds => handle_feeds_key(key.code, app)?,
+            Pane::Items => handle_items_key(key.code, app)?,
+            Pane::Preview => handle_preview_key(key.code, app)?,
+            Pane::Queue => handle_queue_key(key.code, app)?,
+        }
+    }
+    Ok(false)
+}
+
+/// Minimum time between handled key presses outside text entry.
+const KEY_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Handle a key press read from the terminal, returning whether to quit.
+/// Presses within [`KEY_DEBOUNCE`] of the last one are dropped, except while
+/// a popup or type-ahead takes text, so fast typing and pasted text arrive
+/// whole.
+fn key_press(
+    key: KeyEvent,
+    app: &mut AppState,
+    last_key_time: &mut Instant,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let text_entry = app.input_popup.is_some() || app.type_ahead.is_some();
+    if !text_entry && last_key_time.elapsed() < KEY_DEBOUNCE {
+        return Ok(false);
+    }
+    *last_key_time = Instant::now();
+    app.message = None;
+    let quit = handle_key(key, app)?;
+    app.clamp_selection();
+    Ok(quit)
+}
+
+/// Run the event loop.
+fn event_loop(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut AppState,
+    interrupted: &AtomicBool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let tick_rate = Duration::from_millis(250);
+    let mut last_tick = Instant::now();
+    let mut last_key_time = Instant::now();
+
+    while !interrupted.load(Ordering::Relaxed) {
+        while let Ok(status) = app.status_rx.try_recv() {
+            match status {
+                RefreshStatus::Started => {
+                    app.refreshing = true;
+                    app.last_error = None;
+                }
+                RefreshStatus::Failed { url, error } => app.last_error = Some((url, error)),
+                RefreshStatus::Finished { at, new_items } => {
+                    app.refreshing = false;
+                    app.last_refresh = Some(at);
+                    app.new_items = new_items;
+                    app.dirty = true;
+                    if let Some(path) = &app.status_file
+                        && let Err(e) = write_status_file(app, path)
+                    {
+                        app.message = Some(format!("status file: {}", e));
+                    }
+                }
+            }
+        }
+        if app.dirty && app.last_save.elapsed() >= SAVE_DEBOUNCE {
+            app.dirty = false;
+            app.last_save = Instant::now();
+            if let Err(e) = data::save_db(&app.groups.lock().unwrap()) {
+                app.message = Some(format!("save failed: {}", e));
+            }
+        }
+        let unread = data::total_unread(&app.groups.lock().unwrap());
+        if app.title_unread != Some(unread) {
+            app.title_unread = Some(unread);
+            execute!(
+                terminal.backend_mut(),
+                SetTitle(format!("mrss ({unread} unread)"))
+            )?;
+        }
+        app.track_viewed_feed();
+        app.poll_retry();
+        app.poll_schedule();
+        if let Some(reader) = &mut app.reader {
+            reader.poll();
+        }
+        terminal.draw(|f| {
+            ui(f, app);
+            if app.plain {
+                strip_colors(f.buffer_mut());
+            }
+        })?;
+
+        let timeout = tick_rate
+            .checked_sub(last_tick.elapsed())
+            .unwrap_or_else(|| Duration::from_secs(0));
+        if event::poll(timeout)? {
+            let ev = event::read()?;
+            if let Event::Key(key) = ev {
+                if key.kind == KeyEventKind::Press && key_press(key, app, &mut last_key_time)? {
+                    break;
+                }
+            } else if let Event::Paste(text) = ev {
+                // Pasted newlines would otherwise arrive as Enter presses
+                // and submit the popup early.
+                if let Some(popup) = &mut app.input_popup {
+                    popup.buffer.push_str(&text.replace(['\r', '\n'], " "));
+                }
+            } else if let Event::Resize(_, _) = ev {
+                // `draw` resizes the buffers itself and `ui` falls back to a
+                // placeholder when the window is too small, so just redraw.
+                terminal.autoresize()?;
+            }
+        }
+
+        if last_tick.elapsed() >= tick_rate {
+            last_tick = Instant::now();
+        }
+    }
+    Ok(())
+}
+
+/// Smallest terminal size that can show all panes.
+const MIN_WIDTH: u16 = 40;
+const MIN_HEIGHT: u16 = 10;
+
+/// Draw the main UI layout.
+fn ui(f: &mut Frame, app: &AppState) {
+    let area = f.size();
+    if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+        let msg = Paragraph::new("Window too small").wrap(Wrap { trim: true });
+        f.render_widget(msg, area);
+        return;
+    }
+    let outer = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(f.size());
+    let layout = app.config.ui.layout.validated();
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(layout.groups_pct),
+            Constraint::Percentage(layout.feeds_pct),
+            Constraint::Percentage(layout.items_pct),
+        ])
+        .split(outer[0]);
+
+    let groups_guard = app.groups.lock().unwrap();
+    let (group_items, group_selected): (Vec<ListItem>, usize) = if app.config.ui.tree_view {
+        let rows = tree_rows(&groups_guard);
+        let cursor = tree_cursor(app, &groups_guard);
+        let items = rows
+            .iter()
+            .map(|row| match *row {
+                TreeRow::Group(g) => {
+                    let group = &groups_guard[g];
+                    let arrow = match (group.expanded, app.plain) {
+                        (true, false) => "▾",
+                        (false, false) => "▸",
+                        (true, true) => "v",
+                        (false, true) => ">",
+                    };
+                    ListItem::new(format!("{} {} ({})", arrow, group.name, group.unread_count))
+                }
+                TreeRow::Feed(g, f) => {
+                    let feed = &groups_guard[g].feeds[f];
+                    ListItem::new(format!("  {}", feed.title))
+                }
             })
+            .collect();
+        let selected = rows.iter().position(|&r| r == cursor).unwrap_or(0);
+        (items, selected)
+    } else {
+        let items = groups_guard
+            .iter()
+            .map(|g| ListItem::new(g.name.clone()))
+            .collect();
+        (items, app.selected_group)
+    };
+    let group_rows = group_items.len();
+    let group_items_empty = group_items.is_empty();
+    let groups_title = format!("Groups ({})", data::total_unread(&groups_guard));
+    let groups_list = List::new(group_items).block(
+        Block::default()
+            .title(groups_title)
+            .border_style(border_style(app, Pane::Groups))
             .borders(Borders::ALL),
     );
     let mut group_state = ListState::default();
-    if !groups_guard.is_empty() {
-        group_state.select(Some(app.selected_group.min(groups_guard.len() - 1)));
+    if !group_items_empty {
+        group_state.select(Some(group_selected.min(group_rows - 1)));
     }
     f.render_stateful_widget(groups_list, chunks[0], &mut group_state);
 
@@ -623,126 +3011,452 @@ fn ui(f: &mut Frame, app: &AppState) {
         .get(app.selected_group)
         .map(|g| g.feeds.as_slice())
         .unwrap_or(&[]);
-    let feed_items: Vec<ListItem> = feeds
+    let now = Utc::now().timestamp();
+    let shown_feeds = feed_matches(&app.feed_search, feeds);
+    let feed_items: Vec<ListItem> = shown_feeds
         .iter()
-        .map(|f| ListItem::new(f.title.clone()))
+        .map(|&i| feed_row(&feeds[i], now, app.plain))
         .collect();
+    let feeds_title = if app.feed_search.is_empty() {
+        "Feeds".to_string()
+    } else {
+        format!("Feeds [/{}]", app.feed_search)
+    };
     let feeds_list = List::new(feed_items).block(
         Block::default()
-            .title("Feeds")
-            .border_style(if app.focus == Pane::Feeds {
-                Style::default().fg(Color::Yellow)
-            } else {
-                Style::default()
-            })
+            .title(feeds_title)
+            .border_style(border_style(app, Pane::Feeds))
             .borders(Borders::ALL),
     );
     let mut feed_state = ListState::default();
-    if !feeds.is_empty() {
-        feed_state.select(Some(app.selected_feed.min(feeds.len() - 1)));
-    }
+    feed_state.select(shown_feeds.iter().position(|&i| i == app.selected_feed));
     f.render_stateful_widget(feeds_list, chunks[1], &mut feed_state);
 
-    let right_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(chunks[2]);
+    let split = |direction| {
+        Layout::default()
+            .direction(direction)
+            .constraints([
+                Constraint::Percentage(100 - layout.preview_pct),
+                Constraint::Percentage(layout.preview_pct),
+            ])
+            .split(chunks[2])
+    };
+    let position = if app.preview_visible() {
+        layout.preview_position
+    } else {
+        PreviewPosition::Hidden
+    };
+    let (items_area, preview_area) = match position {
+        PreviewPosition::Bottom => {
+            let parts = split(Direction::Vertical);
+            (parts[0], Some(parts[1]))
+        }
+        PreviewPosition::Right => {
+            let parts = split(Direction::Horizontal);
+            (parts[0], Some(parts[1]))
+        }
+        PreviewPosition::Hidden => (chunks[2], None),
+    };
 
-    let indices = if let Some(feed) = feeds.get(app.selected_feed) {
-        let query = app.search.to_lowercase();
-        let mut idx: Vec<usize> = feed
-            .items
-            .iter()
-            .enumerate()
-            .filter(|(_, i)| {
-                (!app.config.ui.unread_only || !i.read)
-                    && (query.is_empty() || i.title.to_lowercase().contains(&query))
-            })
-            .map(|(i, _)| i)
-            .collect();
-        match app.config.ui.sort {
-            crate::config::SortOrder::Date => {
-                idx.sort_by(|&a, &b| feed.items[b].timestamp.cmp(&feed.items[a].timestamp))
-            }
-            crate::config::SortOrder::Title => {
-                idx.sort_by(|&a, &b| feed.items[a].title.cmp(&feed.items[b].title))
+    let indices = match (
+        groups_guard.get(app.selected_group),
+        feeds.get(app.selected_feed),
+    ) {
+        (Some(group), Some(feed)) => feed_indices(app, app.config.ui.read_filter(), group, feed),
+        _ => Vec::new(),
+    };
+    // Items left out by the feed's item limit, noted below the last one.
+    let more = match (
+        groups_guard.get(app.selected_group),
+        feeds.get(app.selected_feed),
+    ) {
+        (Some(group), Some(feed))
+            if item_limit(app, feed).is_some_and(|limit| indices.len() >= limit) =>
+        {
+            unpaged_indices(app, app.config.ui.read_filter(), group, feed).len() - indices.len()
+        }
+        _ => 0,
+    };
+
+    // Entries may span several rows when wrapping; the list state selects by
+    // entry so `selected_item` stays aligned regardless of row heights.
+    let title_width = items_area.width.saturating_sub(2) as usize;
+    let visual = app.visual_range(indices.len());
+    let comfortable = app.config.ui.item_list_density == ItemDensity::Comfortable;
+    // Number of older variants folded into each listed item, and the items
+    // shown because their cluster is expanded.
+    let mut variant_counts: HashMap<usize, usize> = HashMap::new();
+    let mut expanded_variants: HashSet<usize> = HashSet::new();
+    if let Some(threshold) = app.config.ui.collapse_similar_titles
+        && let Some(feed) = feeds.get(app.selected_feed)
+        && let Some(group) = groups_guard.get(app.selected_group)
+    {
+        let all = filtered_indices(app, app.config.ui.read_filter(), group, feed);
+        for (rep, variants) in similar_clusters(feed, &all, threshold) {
+            if !variants.is_empty() {
+                variant_counts.insert(rep, variants.len());
+                expanded_variants.extend(variants);
             }
-            crate::config::SortOrder::Channel => {}
         }
-        idx
+    }
+    // Comfortable rows move the date and feed to their own line.
+    let item_format = if comfortable {
+        "{badge} {title}".into()
     } else {
-        Vec::new()
+        app.config.ui.item_format()
     };
-
     let item_entries: Vec<ListItem> = if let Some(feed) = feeds.get(app.selected_feed) {
+        // The "new" divider goes above the first item the user had already
+        // seen, as long as something newer is listed above it.
+        let date_sorted = !app.config.ui.sort_reverse
+            && groups_guard
+                .get(app.selected_group)
+                .is_some_and(|g| effective_sort(app, g, feed) == SortOrder::Date);
+        let divider = feed
+            .last_seen_timestamp
+            .filter(|_| date_sorted && !feed.group_by_category)
+            .and_then(|seen| {
+                indices
+                    .iter()
+                    .position(|&i| feed.items[i].timestamp <= seen)
+            })
+            .filter(|&pos| pos > 0);
+        // Section sizes, counting the items folded away in collapsed ones.
+        let mut section_sizes: HashMap<Option<&str>, usize> = HashMap::new();
+        if feed.group_by_category
+            && let Some(group) = groups_guard.get(app.selected_group)
+        {
+            for i in filtered_indices(app, app.config.ui.read_filter(), group, feed) {
+                *section_sizes
+                    .entry(item_category(&feed.items[i]))
+                    .or_default() += 1;
+            }
+        }
         indices
             .iter()
-            .map(|&i| {
+            .enumerate()
+            .map(|(pos, &i)| {
                 let item = &feed.items[i];
-                let badge = if item.read { " " } else { "●" };
-                let ts = Utc
-                    .timestamp_opt(item.timestamp, 0)
-                    .single()
-                    .unwrap_or_else(|| Utc.timestamp_opt(0, 0).unwrap())
-                    .format("%m-%d %H:%M")
-                    .to_string();
-                ListItem::new(format!("{} {} {}", badge, ts, item.title))
+                let category = item_category(item);
+                let section_start = feed.group_by_category
+                    && (pos == 0 || item_category(&feed.items[indices[pos - 1]]) != category);
+                let collapsed = section_start
+                    && app
+                        .collapsed_categories
+                        .contains(category.unwrap_or_default());
+                let glyph = |glyph, ascii| plain_glyph(app.plain, glyph, ascii);
+                let badge = match (item.starred, item.read) {
+                    (true, _) => glyph("★", "+"),
+                    (false, false) => glyph(&app.config.ui.unread_glyph, "*"),
+                    (false, true) => glyph(&app.config.ui.read_glyph, " "),
+                };
+                let ts = match Utc.timestamp_opt(item.timestamp, 0).single() {
+                    Some(date) if item.timestamp > 0 => date.format("%m-%d %H:%M").to_string(),
+                    _ => "  undated  ".into(),
+                };
+                let render = |template: &str| {
+                    format_template(template, |token| match token {
+                        "badge" => Some(badge.to_string()),
+                        "date" => Some(ts.clone()),
+                        "title" => Some(item.title.clone()),
+                        "feed" => Some(feed.title.clone()),
+                        _ => None,
+                    })
+                };
+                // `item_format()` guarantees a `{title}` token; the part before
+                // it is the prefix that wrapped lines are indented past.
+                let (lead, tail) = item_format.split_once("{title}").unwrap_or(("", ""));
+                let prefix = render(lead);
+                let variants = match variant_counts.get(&i) {
+                    Some(n) => format!(" (+{n})"),
+                    None if expanded_variants.contains(&i) => " (older)".into(),
+                    None => String::new(),
+                };
+                let rest = format!("{}{}{}", item.title, variants, render(tail));
+                let mut lines: Vec<Line> = if app.config.ui.wrap_titles {
+                    let indent = " ".repeat(prefix.chars().count());
+                    let width = title_width.saturating_sub(indent.len());
+                    wrap_text(&rest, width, 2)
+                        .into_iter()
+                        .enumerate()
+                        .map(|(n, part)| {
+                            let lead = if n == 0 {
+                                prefix.as_str()
+                            } else {
+                                indent.as_str()
+                            };
+                            Line::from(format!("{lead}{part}"))
+                        })
+                        .collect()
+                } else {
+                    vec![Line::from(format!("{}{}", prefix, rest))]
+                };
+                if comfortable {
+                    lines.push(Line::from(Span::styled(
+                        format!(
+                            "{}{} · {}",
+                            " ".repeat(prefix.chars().count()),
+                            ts,
+                            feed.title
+                        ),
+                        Style::default().fg(Color::DarkGray),
+                    )));
+                }
+                if collapsed {
+                    lines.clear();
+                }
+                if section_start {
+                    let arrow = match (collapsed, app.plain) {
+                        (false, false) => "▾",
+                        (true, false) => "▸",
+                        (false, true) => "v",
+                        (true, true) => ">",
+                    };
+                    lines.insert(
+                        0,
+                        Line::from(Span::styled(
+                            format!(
+                                "{} {} ({})",
+                                arrow,
+                                category.unwrap_or("Uncategorized"),
+                                section_sizes.get(&category).copied().unwrap_or_default()
+                            ),
+                            Style::default().add_modifier(Modifier::BOLD),
+                        )),
+                    );
+                }
+                if divider == Some(pos) {
+                    let rule = if app.plain { "-" } else { "─" };
+                    let label = format!("{rule}{rule} seen before ");
+                    let fill = rule.repeat(title_width.saturating_sub(label.chars().count()));
+                    lines.insert(
+                        0,
+                        Line::from(Span::styled(
+                            format!("{label}{fill}"),
+                            Style::default().fg(Color::DarkGray),
+                        )),
+                    );
+                }
+                if more > 0 && pos + 1 == indices.len() {
+                    lines.push(Line::from(Span::styled(
+                        format!(
+                            "{} {} more (press L to load more)",
+                            plain_glyph(app.plain, "…", "..."),
+                            more
+                        ),
+                        Style::default().fg(Color::DarkGray),
+                    )));
+                }
+                let entry = ListItem::new(lines);
+                let style = item_style(item, &app.config.ui, now);
+                if visual.as_ref().is_some_and(|r| r.contains(&pos)) {
+                    entry.style(style.add_modifier(Modifier::REVERSED))
+                } else {
+                    entry.style(style)
+                }
             })
             .collect()
     } else {
         Vec::new()
     };
+    let items_title = match &app.category_filter {
+        Some(c) => format!("Items [{}]", c),
+        None => "Items".into(),
+    };
     let items_list = List::new(item_entries).block(
         Block::default()
-            .title("Items")
-            .border_style(if app.focus == Pane::Items {
-                Style::default().fg(Color::Yellow)
-            } else {
-                Style::default()
-            })
+            .title(items_title)
+            .border_style(border_style(app, Pane::Items))
             .borders(Borders::ALL),
     );
     let mut item_state = ListState::default();
     if !indices.is_empty() {
         item_state.select(Some(app.selected_item.min(indices.len() - 1)));
     }
-    f.render_stateful_widget(items_list, right_chunks[0], &mut item_state);
+    f.render_stateful_widget(items_list, items_area, &mut item_state);
 
-    let preview_lines = if let Some(feed) = feeds.get(app.selected_feed)
+    let preview_lines = if app.focus == Pane::Feeds
+        && let Some(feed) = feeds.get(app.selected_feed)
+    {
+        feed_health_lines(feed)
+    } else if let Some(feed) = feeds.get(app.selected_feed)
         && let Some(&idx) = indices.get(app.selected_item)
     {
         let item = &feed.items[idx];
-        vec![
-            Line::from(item.title.clone()),
-            Line::from(""),
-            Line::from(item.desc.clone()),
-        ]
+        let group = groups_guard
+            .get(app.selected_group)
+            .map_or("", |g| g.name.as_str());
+        let mut source = vec![format!("{} › {}", group, feed.title)];
+        if item.timestamp > 0
+            && let Some(date) = Utc.timestamp_opt(item.timestamp, 0).single()
+        {
+            source.push(date.format("%Y-%m-%d %H:%M").to_string());
+            if item.future_dated {
+                source.push("(dated in the future)".into());
+            }
+        }
+        if let Some(author) = &item.author {
+            source.push(format!("by {}", author));
+        }
+        let mut lines = vec![
+            Line::from(Span::styled(
+                source.join(" · "),
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            )),
+            Line::from(Span::styled(
+                item.title.clone(),
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+        ];
+        if !item.categories.is_empty() {
+            let mut chips = Vec::new();
+            for c in &item.categories {
+                let style = if app.category_filter.as_ref() == Some(c) {
+                    Style::default().fg(Color::Black).bg(Color::Yellow)
+                } else {
+                    Style::default().fg(Color::Black).bg(Color::Gray)
+                };
+                chips.push(Span::styled(format!(" {} ", c), style));
+                chips.push(Span::raw(" "));
+            }
+            lines.push(Line::from(chips));
+        }
+        lines.push(Line::from(""));
+        let full = feed
+            .prefer_full_content
+            .unwrap_or(app.config.ui.prefer_full_content);
+        let body = match app.preview_mode {
+            PreviewMode::Text => html_to_text(item.body(full)),
+            PreviewMode::Raw => item.body(full).to_string(),
+        };
+        let limit = app
+            .config
+            .ui
+            .preview_max_chars
+            .filter(|_| app.preview_full.as_ref() != Some(&item.id));
+        let (body, truncated) = truncate_chars(&body, limit);
+        lines.extend(body.lines().map(|l| Line::from(l.to_string())));
+        if truncated {
+            lines.push(Line::from(Span::styled(
+                "(truncated — F for full)",
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+        if !item.links.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "Links",
+                Style::default().add_modifier(Modifier::BOLD),
+            )));
+            let hints = app
+                .link_hints
+                .as_ref()
+                .map(|typed| (typed, hint_labels(item.links.len())));
+            for (n, (text, href)) in item.links.iter().enumerate() {
+                let line = match &hints {
+                    // Hints still reachable with the letters typed so far
+                    // stand out.
+                    Some((typed, labels)) => {
+                        let style = if labels[n].starts_with(typed.as_str()) {
+                            Style::default()
+                                .fg(Color::Black)
+                                .bg(Color::Yellow)
+                                .add_modifier(Modifier::BOLD)
+                        } else {
+                            Style::default().fg(Color::DarkGray)
+                        };
+                        Line::from(vec![
+                            Span::styled(format!("[{}]", labels[n]), style),
+                            Span::raw(format!(" {} <{}>", text, href)),
+                        ])
+                    }
+                    None => {
+                        let label = if n < 9 {
+                            format!("{}", n + 1)
+                        } else {
+                            " ".into()
+                        };
+                        Line::from(format!("[{}] {} <{}>", label, text, href))
+                    }
+                };
+                lines.push(line);
+            }
+        }
+        lines
     } else {
         vec![Line::from("")]
     };
-    let preview = Paragraph::new(preview_lines).block(
-        Block::default()
-            .title("Preview")
-            .border_style(if app.focus == Pane::Preview {
-                Style::default().fg(Color::Yellow)
-            } else {
-                Style::default()
-            })
-            .borders(Borders::ALL),
-    );
-    f.render_widget(preview, right_chunks[1]);
+    let preview = Paragraph::new(preview_lines)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .title(format!("Preview [{}]", app.preview_mode.label()))
+                .border_style(border_style(app, Pane::Preview))
+                .borders(Borders::ALL),
+        );
+    if let Some(area) = preview_area {
+        f.render_widget(preview, area);
+    }
 
-    let status = if let Some(time) = app.last_refresh {
-        format!(
-            "last refresh: {} | new items: {}",
-            time.format("%H:%M:%S"),
-            app.new_items
+    let status = format_template(&app.config.ui.status_format, |token| match token {
+        "last_refresh" => Some(
+            app.last_refresh
+                .map(|t| t.format("%H:%M:%S").to_string())
+                .unwrap_or_else(|| "never".into()),
+        ),
+        "new_items" => Some(app.new_items.to_string()),
+        "refreshing" => Some(if app.refreshing { "refreshing…" } else { "" }.into()),
+        "last_error" => Some(
+            app.last_error
+                .as_ref()
+                .map(|(url, error)| format!("{}: {}", url, error))
+                .unwrap_or_default(),
+        ),
+        "total_unread" => Some(data::total_unread(&groups_guard).to_string()),
+        "feed_url" => Some(
+            feeds
+                .get(app.selected_feed)
+                .map(|f| f.url.clone())
+                .unwrap_or_default(),
+        ),
+        "selected_index" => Some(if indices.is_empty() {
+            "0".into()
+        } else {
+            (app.selected_item.min(indices.len() - 1) + 1).to_string()
+        }),
+        "total" => Some(indices.len().to_string()),
+        _ => None,
+    });
+    let status = match &app.message {
+        Some(msg) => format!("{} | {}", status, msg),
+        None => status,
+    };
+    let status_bar = Paragraph::new(status);
+    let summary = if app.config.ui.unread_summary {
+        unread_summary(
+            &groups_guard,
+            app.config.ui.unread_summary_groups,
+            app.plain,
         )
     } else {
-        format!("last refresh: never | new items: {}", app.new_items)
+        String::new()
     };
-    let status_bar = Paragraph::new(status);
-    f.render_widget(status_bar, outer[1]);
+    if summary.is_empty() {
+        f.render_widget(status_bar, outer[1]);
+    } else {
+        let width = summary.width() as u16 + 1;
+        let parts = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(width)])
+            .split(outer[1]);
+        f.render_widget(status_bar, parts[0]);
+        f.render_widget(
+            Paragraph::new(summary).alignment(Alignment::Right),
+            parts[1],
+        );
+    }
 
     let keybinds = Paragraph::new(keybind_line(app));
     f.render_widget(keybinds, outer[2]);
@@ -751,48 +3465,530 @@ fn ui(f: &mut Frame, app: &AppState) {
         draw_input_popup(f, f.size(), popup);
     }
     if app.focus == Pane::Queue {
-        draw_queue(f, f.size(), app);
+        draw_queue(f, f.size(), app, &groups_guard);
+    }
+    if let Some(preview) = &app.bulk_preview {
+        draw_bulk_preview(f, f.size(), preview);
+    }
+    if let Some(reader) = &app.reader {
+        reader.draw(f, f.size());
+    }
+    if let Some(jump) = &app.jump {
+        jump.draw(f, centered_rect(80, 70, f.size()));
     }
     if app.show_help {
         draw_help(f, f.size());
     }
 }
 
+/// Cut `text` after `limit` characters, adding an ellipsis. Returns whether
+/// anything was cut.
+fn truncate_chars(text: &str, limit: Option<usize>) -> (Cow<'_, str>, bool) {
+    match limit.and_then(|n| text.char_indices().nth(n)) {
+        Some((end, _)) => (format!("{}…", &text[..end]).into(), true),
+        None => (text.into(), false),
+    }
+}
+
+/// Turn an item title into a file name stem.
+fn file_stem(title: &str) -> String {
+    let stem: String = title
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let stem = stem
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if stem.is_empty() { "item".into() } else { stem }
+}
+
+/// Fill a citation template for `item`. `{author}` falls back to the feed
+/// title and `{date}` is empty for undated items.
+fn citation(template: &str, feed: &Feed, item: &Item) -> String {
+    let date = Utc
+        .timestamp_opt(item.timestamp, 0)
+        .single()
+        .filter(|_| item.timestamp > 0)
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .unwrap_or_default();
+    template
+        .replace("{title}", &item.title)
+        .replace("{author}", item.author.as_deref().unwrap_or(&feed.title))
+        .replace("{date}", &date)
+        .replace("{link}", &item.link)
+        .replace("{feed}", &feed.title)
+}
+
+/// Render an item as a Markdown document for archiving.
+fn item_markdown(feed: &Feed, item: &Item) -> String {
+    let mut doc = format!("# {}\n\n", item.title);
+    if let Some(author) = &item.author {
+        doc.push_str(&format!("*By {}*\n\n", author));
+    }
+    doc.push_str(&format!("- Feed: {}\n", feed.title));
+    if let Some(date) = Utc
+        .timestamp_opt(item.timestamp, 0)
+        .single()
+        .filter(|_| item.timestamp > 0)
+    {
+        doc.push_str(&format!("- Date: {}\n", date.format("%Y-%m-%d %H:%M UTC")));
+    }
+    if !item.link.is_empty() {
+        doc.push_str(&format!("- Link: <{}>\n", item.link));
+    }
+    if !item.categories.is_empty() {
+        doc.push_str(&format!("- Categories: {}\n", item.categories.join(", ")));
+    }
+    doc.push('\n');
+    doc.push_str(html_to_text(item.body(true)).trim());
+    doc.push('\n');
+    if !item.links.is_empty() {
+        doc.push_str("\n## Links\n\n");
+        for (text, href) in &item.links {
+            doc.push_str(&format!("- [{}]({})\n", text, href));
+        }
+    }
+    doc
+}
+
+/// Render an item as a standalone HTML page, keeping the original markup.
+fn item_html(feed: &Feed, item: &Item) -> String {
+    let escape = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    };
+    let mut doc = format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>{0}</title></head>\n<body>\n<h1>{0}</h1>\n",
+        escape(&item.title)
+    );
+    let mut meta = vec![escape(&feed.title)];
+    if let Some(author) = &item.author {
+        meta.push(format!("by {}", escape(author)));
+    }
+    if let Some(date) = Utc
+        .timestamp_opt(item.timestamp, 0)
+        .single()
+        .filter(|_| item.timestamp > 0)
+    {
+        meta.push(date.format("%Y-%m-%d %H:%M UTC").to_string());
+    }
+    doc.push_str(&format!("<p><em>{}</em></p>\n", meta.join(" &middot; ")));
+    if !item.link.is_empty() {
+        doc.push_str(&format!(
+            "<p><a href=\"{0}\">{0}</a></p>\n",
+            escape(&item.link)
+        ));
+    }
+    doc.push_str(item.body(true));
+    doc.push_str("\n</body>\n</html>\n");
+    doc
+}
+
+/// Reduce an HTML fragment to readable plain text.
+///
+/// Block-level tags become line breaks, all other tags are dropped and the
+/// common character entities are decoded.
+fn html_to_text(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            out.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+        let tag = rest[start + 1..start + end]
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        if matches!(
+            tag.as_str(),
+            "br" | "p"
+                | "div"
+                | "li"
+                | "tr"
+                | "h1"
+                | "h2"
+                | "h3"
+                | "h4"
+                | "h5"
+                | "h6"
+                | "blockquote"
+        ) && !out.ends_with('\n')
+        {
+            out.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    let decoded = out
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&");
+    decoded.trim().to_string()
+}
+
+/// Greedily word-wrap `text` to `width` display columns, producing at most
+/// `max_lines` lines. Overlong words are split and overflow on the last line
+/// is replaced with an ellipsis.
+fn wrap_text(text: &str, width: usize, max_lines: usize) -> Vec<String> {
+    if width == 0 || max_lines == 0 {
+        return vec![String::new()];
+    }
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut current_width = 0;
+    for word in text.split_whitespace() {
+        let word_width: usize = word.chars().map(|c| c.width().unwrap_or(0)).sum();
+        let sep = usize::from(!current.is_empty());
+        if current_width + sep + word_width <= width {
+            if sep == 1 {
+                current.push(' ');
+            }
+            current.push_str(word);
+            current_width += sep + word_width;
+            continue;
+        }
+        if !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+            current_width = 0;
+        }
+        for c in word.chars() {
+            let w = c.width().unwrap_or(0);
+            if current_width + w > width {
+                lines.push(std::mem::take(&mut current));
+                current_width = 0;
+            }
+            current.push(c);
+            current_width += w;
+        }
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        let last = &mut lines[max_lines - 1];
+        let mut w: usize = last.chars().map(|c| c.width().unwrap_or(0)).sum();
+        while w + 1 > width {
+            match last.pop() {
+                Some(c) => w -= c.width().unwrap_or(0),
+                None => break,
+            }
+        }
+        last.push('…');
+    }
+    lines
+}
+
+/// Interpolate `{token}` placeholders in a status or item template.
+///
+/// `lookup` returns the value for a token name; unknown tokens and unmatched
+/// braces are rendered literally.
+fn format_template(template: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('}') {
+            Some(end) => {
+                let token = &after[..end];
+                match lookup(token) {
+                    Some(value) => out.push_str(&value),
+                    None => out.push_str(&rest[start..start + end + 2]),
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The `count` groups with the most unread items, e.g. `News:12 Dev:5 …`,
+/// with an ellipsis when more groups have unread items.
+fn unread_summary(groups: &[Group], count: usize, plain: bool) -> String {
+    let mut unread: Vec<&Group> = groups.iter().filter(|g| g.unread_count > 0).collect();
+    unread.sort_by_key(|g| std::cmp::Reverse(g.unread_count));
+    let mut parts: Vec<String> = unread
+        .iter()
+        .take(count)
+        .map(|g| format!("{}:{}", g.name, g.unread_count))
+        .collect();
+    if unread.len() > count && count > 0 {
+        parts.push(plain_glyph(plain, "…", "...").into());
+    }
+    parts.join(" ")
+}
+
+/// Border style of `pane`: highlighted when it has focus, in bold rather than
+/// yellow in plain mode.
+fn border_style(app: &AppState, pane: Pane) -> Style {
+    match (app.focus == pane, app.plain) {
+        (false, _) => Style::default(),
+        (true, false) => Style::default().fg(Color::Yellow),
+        (true, true) => Style::default().add_modifier(Modifier::BOLD),
+    }
+}
+
+/// `glyph`, or `ascii` in plain mode unless `glyph` is already ASCII.
+fn plain_glyph<'a>(plain: bool, glyph: &'a str, ascii: &'a str) -> &'a str {
+    if plain && !glyph.is_ascii() {
+        ascii
+    } else {
+        glyph
+    }
+}
+
+/// Drop all colors from a drawn frame for plain mode. Cells that relied on a
+/// background color (category chips, highlights) are shown reversed instead.
+fn strip_colors(buf: &mut Buffer) {
+    for cell in &mut buf.content {
+        if cell.bg != Color::Reset {
+            cell.modifier.insert(Modifier::REVERSED);
+        }
+        cell.fg = Color::Reset;
+        cell.bg = Color::Reset;
+    }
+}
+
+/// Build a Feeds pane row with pin/disabled/empty markers and rate-limit
+/// status.
+fn feed_row(feed: &Feed, now: i64, plain: bool) -> ListItem<'static> {
+    let mut spans = Vec::new();
+    if feed.pinned {
+        let pin = if plain { "! " } else { "⚑ " };
+        spans.push(Span::styled(pin, Style::default().fg(Color::Yellow)));
+    }
+    if feed.disabled {
+        let cross = if plain { "x " } else { "✗ " };
+        spans.push(Span::styled(cross, Style::default().fg(Color::Red)));
+        spans.push(Span::styled(
+            feed.title.clone(),
+            Style::default().fg(Color::DarkGray),
+        ));
+    } else if feed.unsubscribed {
+        spans.push(Span::styled(
+            format!("{} (archived)", feed.title),
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
+        ));
+    } else {
+        spans.push(Span::raw(feed.title.clone()));
+    }
+    if feed.empty_fetch {
+        spans.push(Span::styled(" (empty)", Style::default().fg(Color::Yellow)));
+    }
+    if let Some(until) = feed.rate_limited_until.filter(|&t| t > now) {
+        let until = Utc
+            .timestamp_opt(until, 0)
+            .single()
+            .map(|t| t.format("%H:%M").to_string())
+            .unwrap_or_default();
+        spans.push(Span::styled(
+            format!(" (rate-limited until {})", until),
+            Style::default().fg(Color::Red),
+        ));
+    }
+    ListItem::new(Line::from(spans))
+}
+
+/// Style for an item row: unread items are bold in the theme's accent colour,
+/// read ones are dimmed. With `color_by_age`, today's items are emphasized
+/// and those older than a week dimmed. Hidden items, listed only while
+/// restoring them, are struck through.
+fn item_style(item: &Item, ui: &crate::config::Ui, now: i64) -> Style {
+    let (accent, muted) = match ui.theme {
+        Theme::Dark => (Color::Cyan, Color::DarkGray),
+        Theme::Light => (Color::Blue, Color::Gray),
+    };
+    let mut style = if item.read {
+        Style::default().fg(muted)
+    } else {
+        Style::default().fg(accent).add_modifier(Modifier::BOLD)
+    };
+    if ui.color_by_age && item.timestamp != 0 {
+        const DAY: i64 = 24 * 60 * 60;
+        let age = now - item.timestamp;
+        if age < DAY {
+            style = style.add_modifier(Modifier::BOLD);
+        } else if age > 7 * DAY {
+            style = style.add_modifier(Modifier::DIM);
+        }
+    }
+    if item.hidden {
+        style = style.add_modifier(Modifier::CROSSED_OUT);
+    }
+    style
+}
+
+/// Describe a feed's fetch health for the Preview pane.
+fn feed_health_lines(feed: &Feed) -> Vec<Line<'static>> {
+    let fmt_time = |ts: i64| {
+        Utc.timestamp_opt(ts, 0)
+            .single()
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default()
+    };
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    vec![
+        Line::from(Span::styled(feed.title.clone(), bold)),
+        Line::from(feed.url.clone()),
+        Line::from(""),
+        Line::from(format!(
+            "Last success: {}",
+            feed.last_fetch.map_or_else(|| "never".into(), fmt_time)
+        )),
+        Line::from(format!("Error streak: {}", feed.error_streak)),
+        Line::from(match &feed.last_error {
+            Some(error) => format!("Last error: {} (R to retry)", error),
+            None => String::new(),
+        }),
+        Line::from(format!(
+            "Last status: {}",
+            feed.last_status
+                .map_or_else(|| "-".into(), |s| s.to_string())
+        )),
+        Line::from(format!(
+            "Not modified (304): {}",
+            if feed.not_modified { "yes" } else { "no" }
+        )),
+        Line::from(format!(
+            "Parser: {}",
+            if feed.lenient_parse {
+                "lenient"
+            } else {
+                "strict"
+            }
+        )),
+        Line::from(format!(
+            "Items: {} ({} unread)",
+            feed.total_count(),
+            feed.unread_count()
+        )),
+        Line::from(match (feed.missing_links, feed.missing_dates) {
+            (0, 0) => String::new(),
+            (links, 0) => format!("{} entries missing links", links),
+            (0, dates) => format!("{} entries missing dates", dates),
+            (links, dates) => format!("{} entries missing links, {} missing dates", links, dates),
+        }),
+        Line::from(if feed.empty_fetch {
+            "Last fetch returned no entries; showing previous items"
+        } else {
+            ""
+        }),
+        Line::from(match feed.update_interval {
+            Some(secs) if secs % 3600 == 0 => format!("Update hint: every {}h", secs / 3600),
+            Some(secs) => format!("Update hint: every {}m", secs / 60),
+            None => "Update hint: none".into(),
+        }),
+        Line::from(format!(
+            "Homepage: {}",
+            feed.home_url.as_deref().unwrap_or("unknown")
+        )),
+        Line::from(format!(
+            "Open links: {}",
+            match feed.open_in_background {
+                None => "default",
+                Some(true) => "background",
+                Some(false) => "foreground",
+            }
+        )),
+        Line::from(format!(
+            "Preview: {}",
+            match feed.prefer_full_content {
+                None => "default",
+                Some(true) => "full content",
+                Some(false) => "summary",
+            }
+        )),
+        Line::from(if feed.disabled {
+            "Disabled after repeated failures (e to re-enable)"
+        } else if feed.unsubscribed {
+            "Unsubscribed: items kept, not fetched (D to resubscribe)"
+        } else {
+            ""
+        }),
+    ]
+}
+
 /// Build the keybind hint line for the status bar.
 fn keybind_line(app: &AppState) -> Line<'static> {
     let mut parts: Vec<String> = vec![
         "Tab:Next pane".into(),
         "BackTab:Prev pane".into(),
-        if app.config.ui.unread_only {
-            "u:Show all".into()
-        } else {
-            "u:Unread only".into()
-        },
+        format!("u:Show {}", app.config.ui.read_filter().next().label()),
         "Ctrl+f:Search".into(),
+        "Ctrl+g:Go to".into(),
+        "n/N:Next/prev unread".into(),
         "?:Help".into(),
         "Q:Queue".into(),
+        "Ctrl+q:Quit".into(),
     ];
 
-    if !matches!(app.focus, Pane::Items | Pane::Queue) {
-        parts.push("q:Quit".into());
-    }
-
     match app.focus {
         Pane::Groups => {
             parts.extend([
+                if app.config.ui.tree_view {
+                    "Enter:Expand".into()
+                } else {
+                    "Right:Feeds".into()
+                },
                 "a:Add group".into(),
                 "d:Del group".into(),
                 "r:Rename".into(),
+                "y:Copy OPML".into(),
                 "A:Mark read".into(),
                 "O:Open unread".into(),
+                "U:Queue unread".into(),
+                "b:Mark read older than".into(),
+                "s:Sort".into(),
+                "':Find".into(),
             ]);
         }
         Pane::Feeds => {
             parts.extend([
                 "a:Add feed".into(),
+                "+:Add several".into(),
+                "E:Edit URL".into(),
                 "d:Del feed".into(),
+                "D:Unsubscribe".into(),
                 "A:Mark read".into(),
                 "O:Open unread".into(),
+                "U:Queue unread".into(),
+                "b:Mark read older than".into(),
+                "s:Sort".into(),
+                "e:Re-enable".into(),
+                "R:Retry".into(),
+                "p:Pin".into(),
+                "B:Background open".into(),
+                "C:Full content".into(),
+                "y:Copy URL".into(),
+                "H:Homepage".into(),
+                "':Find".into(),
             ]);
         }
         Pane::Items => {
@@ -801,14 +3997,42 @@ fn keybind_line(app: &AppState) -> Line<'static> {
                 "Space:Toggle read".into(),
                 "m:Mark read".into(),
                 "M:Mark unread".into(),
-                "q:Queue".into(),
+                "q:Toggle queued".into(),
                 "Delete:Dequeue".into(),
+                "1-9:Open link".into(),
+                "v:Preview mode".into(),
+                "c:Category".into(),
+                "G:Group by category".into(),
+                "e:Similar titles".into(),
+                "x:Hide".into(),
+                "V:Select range".into(),
             ]);
         }
         Pane::Queue => {
-            parts.extend(["Enter:Open all".into(), "Esc/q:Close".into()]);
+            parts.extend([
+                if app.show_starred {
+                    "Enter:Open"
+                } else {
+                    "Enter:Open all"
+                }
+                .into(),
+                "Left/Right:Queue/Starred".into(),
+                if app.show_starred {
+                    "s:Move to queue"
+                } else {
+                    "s:Move to starred"
+                }
+                .into(),
+                "Esc:Close".into(),
+            ]);
+        }
+        Pane::Preview => {
+            parts.extend([
+                "1-9:Open link".into(),
+                "f:Link hints".into(),
+                "v:Preview mode".into(),
+            ]);
         }
-        Pane::Preview => {}
     }
 
     Line::from(parts.join(" | "))
@@ -832,14 +4056,34 @@ fn draw_help(f: &mut Frame, area: Rect) {
         )]),
         Line::from(" Tab/BackTab: Switch panes"),
         Line::from(" Arrow keys: Navigate"),
+        Line::from(" n/N: Next/prev unread item"),
+        Line::from(" P: Toggle preview pane"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Group management",
             Style::default().add_modifier(Modifier::BOLD),
         )]),
         Line::from(" a: Add group"),
+        Line::from(" I: Import OPML"),
+        Line::from(" y: Copy group as OPML"),
+        Line::from(" X/J: Export/import JSON backup"),
         Line::from(" d: Delete group"),
         Line::from(" r: Rename group"),
+        Line::from(" Enter/Space: Expand (tree view)"),
+        Line::from(" s: Cycle group/feed sort"),
+        Line::from(" ': Find group/feed by typing its name"),
+        Line::from(" /: Filter feeds (Feeds)"),
+        Line::from(" +: Add several feeds (Feeds)"),
+        Line::from(" E: Edit feed URL (shown in preview)"),
+        Line::from(" b: Mark read older than age"),
+        Line::from(" e: Re-enable disabled feed"),
+        Line::from(" D: Unsubscribe keeping items / resubscribe"),
+        Line::from(" R: Retry feed now"),
+        Line::from(" p: Pin feed to top"),
+        Line::from(" B: Toggle opening feed links in background"),
+        Line::from(" y: Copy feed URL"),
+        Line::from(" C: Toggle previewing full content or summaries"),
+        Line::from(" H: Open feed homepage"),
     ];
 
     let right_lines = vec![
@@ -850,17 +4094,36 @@ fn draw_help(f: &mut Frame, area: Rect) {
         Line::from(" Enter: Open item"),
         Line::from(" Space: Toggle read"),
         Line::from(" m/M: Mark read/unread"),
-        Line::from(" Q: Queue"),
+        Line::from(" q: Toggle queued (Items)"),
+        Line::from(" U: Queue unread of group/feed"),
+        Line::from(" *: Toggle starred"),
+        Line::from(" x/X: Hide item / show hidden"),
+        Line::from(" s: Move queue <-> starred (Queue)"),
         Line::from(" Delete: Remove from queue"),
+        Line::from(" 1-9: Open numbered link"),
+        Line::from(" f: Pick a link by hint (Preview)"),
+        Line::from(" v: Cycle preview text/raw"),
+        Line::from(" F: Show full description"),
+        Line::from(" w: Export item to Markdown/HTML"),
+        Line::from(" y/Y: Copy item / cycle copy style"),
+        Line::from(" c: Cycle category filter"),
+        Line::from(" G/z: Group by category / fold section"),
+        Line::from(" e: Expand similar titles"),
+        Line::from(" L: Load more items"),
+        Line::from(" V: Select range (then m/q)"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Global commands",
             Style::default().add_modifier(Modifier::BOLD),
         )]),
-        Line::from(" u: Toggle unread only"),
+        Line::from(" u: Cycle all/unread/read"),
+        Line::from(" S: Reverse sort direction"),
         Line::from(" Ctrl+f: Search"),
+        Line::from(" Ctrl+g: Go to any item, feed or group"),
+        Line::from(" Ctrl+a: Mark everything read"),
         Line::from(" ?: Toggle help"),
-        Line::from(" q: Quit"),
+        Line::from(" Q: Show queue (Esc closes)"),
+        Line::from(" Ctrl+q: Quit (Ctrl+c also works)"),
     ];
 
     let left = Paragraph::new(left_lines);
@@ -877,41 +4140,648 @@ fn draw_input_popup(f: &mut Frame, area: Rect, popup: &InputPopup) {
         .title(popup.title.as_str())
         .borders(Borders::ALL);
     let paragraph = Paragraph::new(popup.buffer.as_str()).block(block);
-    let popup_area = centered_rect(60, 20, area);
+    // Keep the lists visible while searching so results narrow live.
+    let popup_area =
+        if let InputAction::Search { .. } | InputAction::FeedSearch { .. } = popup.submit {
+            let height = area.height.min(3);
+            Rect {
+                y: area.bottom() - height,
+                height,
+                ..area
+            }
+        } else {
+            centered_rect(60, 20, area)
+        };
     f.render_widget(Clear, popup_area);
     f.render_widget(paragraph, popup_area);
 }
 
-fn draw_queue(f: &mut Frame, area: Rect, app: &AppState) {
-    let block = Block::default().title("Queue").borders(Borders::ALL);
-    let items: Vec<ListItem> = app
-        .queue
+fn draw_bulk_preview(f: &mut Frame, area: Rect, preview: &BulkPreview) {
+    let block = Block::default()
+        .title(preview.title.as_str())
+        .borders(Borders::ALL);
+    let mut lines: Vec<Line> = preview
+        .lines
         .iter()
-        .map(|i| ListItem::new(i.title.clone()))
+        .map(|l| Line::from(l.as_str()))
         .collect();
-    let list = List::new(items).block(block);
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Enter/y: confirm  Esc/n: cancel",
+        Style::default().add_modifier(Modifier::BOLD),
+    )));
+    let popup_area = centered_rect(60, 50, area);
+    f.render_widget(Clear, popup_area);
+    f.render_widget(Paragraph::new(lines).block(block), popup_area);
+}
+
+fn draw_queue(f: &mut Frame, area: Rect, app: &AppState, groups: &[Group]) {
+    let starred = starred_items(groups);
+    let title = if app.show_starred {
+        format!(
+            "Queue ({}) | [Starred ({})]",
+            app.queue.len(),
+            starred.len()
+        )
+    } else {
+        format!(
+            "[Queue ({})] | Starred ({})",
+            app.queue.len(),
+            starred.len()
+        )
+    };
+    let block = Block::default().title(title).borders(Borders::ALL);
+    let items: Vec<ListItem> = if app.show_starred {
+        starred
+            .iter()
+            .map(|&(g, f, i)| ListItem::new(groups[g].feeds[f].items[i].title.clone()))
+            .collect()
+    } else {
+        app.queue
+            .iter()
+            .map(|i| ListItem::new(i.title.clone()))
+            .collect()
+    };
+    let mut state = ListState::default();
+    if !items.is_empty() {
+        state.select(Some(app.queue_selected.min(items.len() - 1)));
+    }
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
     let popup_area = centered_rect(60, 60, area);
     f.render_widget(Clear, popup_area);
-    f.render_widget(list, popup_area);
+    f.render_stateful_widget(list, popup_area, &mut state);
 }
 
 /// Helper to create a centered rect using up certain percentage of the available space.
+///
+/// The result is always at least 1x1 (unless `r` itself is empty) and never
+/// extends beyond `r`.
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
-        ])
-        .split(r);
+    let scale = |len: u16, pct: u16| -> u16 {
+        let scaled = (u32::from(len) * u32::from(pct.min(100)) / 100) as u16;
+        scaled.max(1).min(len)
+    };
+    let width = scale(r.width, percent_x);
+    let height = scale(r.height, percent_y);
+    Rect {
+        x: r.x + (r.width - width) / 2,
+        y: r.y + (r.height - height) / 2,
+        width,
+        height,
+    }
+}
 
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(popup_layout[1])[1]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ReadFilter;
+    use ratatui::backend::TestBackend;
+
+    /// An item titled "Item <id>". Earlier letters are newer, so feeds list
+    /// their items alphabetically.
+    fn item(id: &str, read: bool) -> Item {
+        let mut item: Item = serde_json::from_value(serde_json::json!({
+            "id": id,
+            "title": format!("Item {}", id),
+            "link": format!("https://example.com/{}", id),
+            "timestamp": 1_700_000_000 - i64::from(id.as_bytes()[0]),
+        }))
+        .unwrap();
+        item.read = read;
+        item
+    }
+
+    fn feed(title: &str, items: Vec<Item>) -> Feed {
+        Feed {
+            url: format!("https://example.com/{}.xml", title.to_lowercase()),
+            title: title.into(),
+            items,
+            ..Feed::default()
+        }
+    }
+
+    fn group(name: &str, feeds: Vec<Feed>) -> Group {
+        let mut group = Group {
+            name: name.into(),
+            feeds,
+            ..Group::default()
+        };
+        group.update_unread();
+        group
+    }
+
+    /// State over `groups` that doesn't read a saved session.
+    fn app(groups: Vec<Group>) -> AppState {
+        let mut config = Config::default();
+        config.ui.restore_session = false;
+        AppState::detached(config, Arc::new(Mutex::new(groups)))
+    }
+
+    /// One group with a feed holding a read and an unread item.
+    fn news() -> AppState {
+        app(vec![group(
+            "News",
+            vec![feed("World", vec![item("a", false), item("b", true)])],
+        )])
+    }
+
+    /// Send a key press, returning whether it asked to quit.
+    fn press(app: &mut AppState, code: KeyCode) -> bool {
+        handle_key(KeyEvent::new(code, KeyModifiers::NONE), app).unwrap()
+    }
+
+    fn ctrl(app: &mut AppState, c: char) -> bool {
+        handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL), app).unwrap()
+    }
+
+    fn type_text(app: &mut AppState, text: &str) {
+        for c in text.chars() {
+            assert!(!press(app, KeyCode::Char(c)));
+        }
+    }
+
+    /// Draw `ui` on a `width` x `height` buffer and return its rows.
+    fn render(app: &AppState, width: u16, height: u16) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| ui(f, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content
+            .chunks(width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn renders_empty_database() {
+        let screen = render(&app(Vec::new()), 100, 30);
+        assert!(screen.contains("Groups (0)"), "{screen}");
+        assert!(screen.contains("Feeds"), "{screen}");
+        assert!(screen.contains("Items"), "{screen}");
+    }
+
+    #[test]
+    fn renders_one_group() {
+        let screen = render(&news(), 120, 30);
+        assert!(screen.contains("Groups (1)"), "{screen}");
+        assert!(screen.contains("News"), "{screen}");
+        assert!(screen.contains("World"), "{screen}");
+        assert!(screen.contains("Item a"), "{screen}");
+    }
+
+    #[test]
+    fn unread_filter_hides_read_items() {
+        let mut app = news();
+        app.config.ui.read_filter = Some(ReadFilter::Unread);
+        let screen = render(&app, 120, 30);
+        assert!(screen.contains("Item a"), "{screen}");
+        assert!(!screen.contains("Item b"), "{screen}");
+
+        app.config.ui.read_filter = Some(ReadFilter::All);
+        let screen = render(&app, 120, 30);
+        assert!(screen.contains("Item a"), "{screen}");
+        assert!(screen.contains("Item b"), "{screen}");
+    }
+
+    #[test]
+    fn renders_open_queue() {
+        let mut app = news();
+        app.queue.push(item("q", false));
+        app.focus = Pane::Queue;
+        let screen = render(&app, 120, 30);
+        assert!(screen.contains("[Queue (1)]"), "{screen}");
+        assert!(screen.contains("Item q"), "{screen}");
+    }
+
+    #[test]
+    fn renders_help_overlay() {
+        let mut app = news();
+        assert!(!render(&app, 120, 40).contains("Switch panes"));
+        app.show_help = true;
+        let screen = render(&app, 120, 40);
+        assert!(screen.contains("Tab/BackTab: Switch panes"), "{screen}");
+    }
+
+    #[test]
+    fn tiny_windows_do_not_panic() {
+        for (width, height) in [(1, 1), (10, 3)] {
+            render(&app(Vec::new()), width, height);
+            render(&news(), width, height);
+        }
+        assert!(render(&news(), 10, 3).contains("Window"));
+    }
+
+    #[test]
+    fn search_popup_captures_global_keys() {
+        let mut app = news();
+        app.focus = Pane::Items;
+        let filter = app.config.ui.read_filter();
+        ctrl(&mut app, 'f');
+        type_text(&mut app, "u?Qq");
+        assert!(!press(&mut app, KeyCode::Tab));
+        assert!(!press(&mut app, KeyCode::BackTab));
+
+        let popup = app.input_popup.as_ref().expect("search still open");
+        assert_eq!(popup.buffer, "u?Qq");
+        assert_eq!(app.search, "u?Qq");
+        assert_eq!(app.focus, Pane::Items);
+        assert_eq!(app.config.ui.read_filter(), filter);
+        assert!(!app.show_help);
+
+        // Esc restores the search from before the popup.
+        press(&mut app, KeyCode::Esc);
+        assert!(app.input_popup.is_none());
+        assert_eq!(app.search, "");
+    }
+
+    #[test]
+    fn feed_url_popup_takes_letters_used_as_commands() {
+        let mut app = news();
+        app.focus = Pane::Feeds;
+        press(&mut app, KeyCode::Char('a'));
+        type_text(&mut app, "dqU");
+        assert_eq!(app.input_popup.as_ref().unwrap().buffer, "dqU");
+        assert_eq!(app.groups.lock().unwrap()[0].feeds.len(), 1);
+        assert!(app.queue.is_empty());
+    }
+
+    /// Whether every group's cached unread count matches its feeds.
+    fn counts_in_sync(app: &AppState) -> bool {
+        let groups = app.groups.lock().unwrap();
+        let total: usize = groups
+            .iter()
+            .flat_map(|g| &g.feeds)
+            .map(Feed::unread_count)
+            .sum();
+        data::total_unread(&groups) == total
+            && groups
+                .iter()
+                .all(|g| g.unread_count == g.feeds.iter().map(Feed::unread_count).sum::<usize>())
+    }
+
+    #[test]
+    fn unread_counts_stay_in_sync_with_items() {
+        let mut app = app(vec![group(
+            "News",
+            vec![
+                feed("World", vec![item("a", false), item("b", false)]),
+                feed("Local", vec![item("c", false)]),
+            ],
+        )]);
+        app.config.ui.read_filter = Some(ReadFilter::All);
+        app.focus = Pane::Items;
+        for code in ['m', 'M', ' ', 'x'] {
+            press(&mut app, KeyCode::Char(code));
+            assert!(counts_in_sync(&app), "after '{code}'");
+        }
+        assert_eq!(app.groups.lock().unwrap()[0].unread_count, 2);
+
+        press(&mut app, KeyCode::Left);
+        press(&mut app, KeyCode::Char('A'));
+        assert!(counts_in_sync(&app));
+        assert_eq!(app.groups.lock().unwrap()[0].unread_count, 1);
+        let groups = app.groups.lock().unwrap();
+        assert_eq!(groups[0].feed_count(), 2);
+        assert_eq!(groups[0].feeds[0].total_count(), 2);
+    }
+
+    #[test]
+    fn only_changes_mark_the_database_dirty() {
+        let mut app = news();
+        app.config.ui.read_filter = Some(ReadFilter::All);
+        for code in [
+            KeyCode::Tab,
+            KeyCode::Down,
+            KeyCode::Up,
+            KeyCode::Char('?'),
+            KeyCode::Char('?'),
+            KeyCode::Char('v'),
+        ] {
+            press(&mut app, code);
+            assert!(!app.dirty, "after {code:?}");
+        }
+        ctrl(&mut app, 'f');
+        type_text(&mut app, "a");
+        press(&mut app, KeyCode::Enter);
+        assert!(!app.dirty);
+
+        assert_eq!(app.focus, Pane::Feeds);
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Char('m'));
+        assert!(app.dirty);
+    }
+
+    #[test]
+    fn rejected_popup_input_leaves_the_database_clean() {
+        let mut app = app(vec![group("News", vec![]), group("Tech", vec![])]);
+        let popup = |buffer: &str, submit| InputPopup {
+            title: String::new(),
+            buffer: buffer.into(),
+            submit,
+        };
+        for rejected in [
+            popup("  ", InputAction::AddGroup),
+            popup("news", InputAction::RenameGroup(1)),
+            popup(
+                "soon",
+                InputAction::MarkReadOlder {
+                    group: 0,
+                    feed: None,
+                },
+            ),
+            popup("/nonexistent/backup.json", InputAction::ImportJson),
+        ] {
+            let input = rejected.buffer.clone();
+            submit_popup(rejected, &mut app);
+            assert!(!app.dirty, "after {input:?}");
+        }
+
+        submit_popup(popup("Daily", InputAction::RenameGroup(1)), &mut app);
+        assert!(app.dirty);
+    }
+
+    fn selected_title(app: &AppState) -> Option<String> {
+        selected_item(app).map(|item| item.title)
+    }
+
+    #[test]
+    fn mark_read_under_unread_filter_keeps_the_cursor_in_place() {
+        let items = ["a", "b", "c", "d"].map(|id| item(id, false)).to_vec();
+        let mut app = app(vec![group("News", vec![feed("World", items)])]);
+        app.config.ui.read_filter = Some(ReadFilter::Unread);
+        app.focus = Pane::Items;
+        app.selected_item = 1;
+
+        // The next item moves up under the cursor.
+        press(&mut app, KeyCode::Char('m'));
+        assert_eq!(selected_title(&app).as_deref(), Some("Item c"));
+        assert_eq!(app.selected_item, 1);
+
+        // At the end of the list the cursor falls back to the previous one.
+        press(&mut app, KeyCode::Down);
+        assert_eq!(selected_title(&app).as_deref(), Some("Item d"));
+        press(&mut app, KeyCode::Char('m'));
+        assert_eq!(selected_title(&app).as_deref(), Some("Item c"));
+
+        press(&mut app, KeyCode::Char('m'));
+        press(&mut app, KeyCode::Char('m'));
+        assert_eq!(selected_title(&app), None);
+        assert_eq!(app.selected_item, 0);
+    }
+
+    #[test]
+    fn marking_unread_under_read_filter_keeps_the_cursor_in_place() {
+        let items = ["a", "b", "c"].map(|id| item(id, true)).to_vec();
+        let mut app = app(vec![group("News", vec![feed("World", items)])]);
+        app.config.ui.read_filter = Some(ReadFilter::Read);
+        app.focus = Pane::Items;
+        press(&mut app, KeyCode::Char('M'));
+        assert_eq!(selected_title(&app).as_deref(), Some("Item b"));
+        assert_eq!(app.selected_item, 0);
+    }
+
+    #[test]
+    fn deleting_down_to_empty_keeps_the_selection_valid() {
+        let mut app = app(vec![
+            group("News", vec![feed("World", vec![item("a", false)])]),
+            group("Tech", vec![feed("Rust", vec![item("b", false)])]),
+        ]);
+        app.config.ui.confirm_delete = false;
+        app.selected_group = 1;
+        app.focus = Pane::Feeds;
+        app.selected_item = 3;
+
+        // The last feed of a group: back to Groups.
+        press(&mut app, KeyCode::Char('d'));
+        assert!(app.groups.lock().unwrap()[1].feeds.is_empty());
+        assert_eq!(app.focus, Pane::Groups);
+        assert_eq!((app.selected_feed, app.selected_item), (0, 0));
+        render(&app, 100, 30);
+
+        // The last group in the list: the selection moves up.
+        press(&mut app, KeyCode::Char('d'));
+        assert_eq!(app.groups.lock().unwrap().len(), 1);
+        assert_eq!(app.selected_group, 0);
+        render(&app, 100, 30);
+
+        press(&mut app, KeyCode::Char('d'));
+        assert!(app.groups.lock().unwrap().is_empty());
+        assert_eq!(app.selected_group, 0);
+        render(&app, 100, 30);
+        for code in [
+            KeyCode::Down,
+            KeyCode::Right,
+            KeyCode::Right,
+            KeyCode::Char('m'),
+        ] {
+            press(&mut app, code);
+            render(&app, 100, 30);
+        }
+    }
+
+    #[test]
+    fn deleting_the_last_feed_in_a_list_selects_the_previous_one() {
+        let mut app = app(vec![group(
+            "News",
+            vec![
+                feed("World", vec![item("a", false)]),
+                feed("Local", vec![item("b", false)]),
+            ],
+        )]);
+        app.config.ui.confirm_delete = false;
+        app.focus = Pane::Feeds;
+        app.selected_feed = 1;
+        press(&mut app, KeyCode::Char('d'));
+        assert_eq!(app.selected_feed, 0);
+        assert_eq!(app.focus, Pane::Feeds);
+        assert_eq!(selected_title(&app).as_deref(), Some("Item a"));
+    }
+
+    #[test]
+    fn delete_asks_first_when_confirm_delete_is_on() {
+        let mut app = news();
+        app.focus = Pane::Feeds;
+        press(&mut app, KeyCode::Char('d'));
+        assert!(app.bulk_preview.is_some());
+        press(&mut app, KeyCode::Char('n'));
+        assert!(app.bulk_preview.is_none());
+        assert_eq!(app.groups.lock().unwrap()[0].feeds.len(), 1);
+
+        press(&mut app, KeyCode::Char('d'));
+        press(&mut app, KeyCode::Char('y'));
+        assert!(app.groups.lock().unwrap()[0].feeds.is_empty());
+        assert_eq!(app.focus, Pane::Groups);
+        assert!(app.dirty);
+    }
+
+    #[test]
+    fn adding_a_duplicate_feed_warns_before_asking_for_the_group() {
+        let mut app = news();
+        app.focus = Pane::Feeds;
+        press(&mut app, KeyCode::Char('a'));
+        type_text(&mut app, "HTTP://Example.com/world.xml/");
+        press(&mut app, KeyCode::Enter);
+        let popup = app.input_popup.as_ref().expect("group popup");
+        assert!(
+            popup.title.contains("Already subscribed in 'News'"),
+            "{}",
+            popup.title
+        );
+
+        press(&mut app, KeyCode::Esc);
+        assert!(app.input_popup.is_none());
+        assert_eq!(app.groups.lock().unwrap()[0].feeds.len(), 1);
+    }
+
+    #[test]
+    fn added_feeds_go_to_the_group_named_in_the_popup() {
+        let mut app = news();
+        app.focus = Pane::Feeds;
+        press(&mut app, KeyCode::Char('a'));
+        type_text(&mut app, "example.org/rss");
+        press(&mut app, KeyCode::Enter);
+        let popup = app.input_popup.as_ref().expect("group popup");
+        assert_eq!(popup.buffer, "News");
+
+        for _ in 0..4 {
+            press(&mut app, KeyCode::Backspace);
+        }
+        type_text(&mut app, "Tech");
+        press(&mut app, KeyCode::Enter);
+        let groups = app.groups.lock().unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[1].name, "Tech");
+        assert_eq!(groups[1].feeds[0].url, "https://example.org/rss");
+        assert_eq!((app.selected_group, app.selected_feed), (1, 0));
+    }
+
+    #[test]
+    fn renaming_a_group_to_an_existing_name_is_refused() {
+        let mut app = app(vec![group("News", vec![]), group("Tech", vec![])]);
+        app.focus = Pane::Groups;
+        app.selected_group = 1;
+        press(&mut app, KeyCode::Char('r'));
+        assert_eq!(app.input_popup.as_ref().unwrap().buffer, "Tech");
+
+        for _ in 0..4 {
+            press(&mut app, KeyCode::Backspace);
+        }
+        type_text(&mut app, " news ");
+        press(&mut app, KeyCode::Enter);
+        assert!(app.message.as_deref().unwrap().contains("already exists"));
+        assert_eq!(app.groups.lock().unwrap()[1].name, "Tech");
+
+        press(&mut app, KeyCode::Char('r'));
+        type_text(&mut app, " Daily");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.groups.lock().unwrap()[1].name, "Tech Daily");
+    }
+
+    #[test]
+    fn queue_is_rebuilt_from_saved_flags_without_duplicates() {
+        let mut first = item("a", false);
+        first.queued = true;
+        first.flags_updated = 2;
+        let mut second = item("b", false);
+        second.queued = true;
+        second.flags_updated = 1;
+        let mut app = app(vec![group(
+            "News",
+            vec![feed("World", vec![first, second, item("c", false)])],
+        )]);
+        let queue: Vec<&str> = app.queue.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(queue, ["b", "a"]);
+
+        app.focus = Pane::Groups;
+        press(&mut app, KeyCode::Char('U'));
+        let queue: Vec<&str> = app.queue.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(queue, ["b", "a", "c"]);
+        assert!(app.groups.lock().unwrap()[0].feeds[0].items[2].queued);
+    }
+
+    #[test]
+    fn keys_typed_back_to_back_into_a_popup_all_arrive() {
+        let mut app = news();
+        app.focus = Pane::Feeds;
+        let mut last_key_time = Instant::now();
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        key_press(key('a'), &mut app, &mut last_key_time).unwrap();
+        assert!(
+            app.input_popup.is_none(),
+            "debounced right after the last key"
+        );
+
+        last_key_time -= KEY_DEBOUNCE;
+        key_press(key('a'), &mut app, &mut last_key_time).unwrap();
+        for c in "example.org/feed".chars() {
+            key_press(key(c), &mut app, &mut last_key_time).unwrap();
+        }
+        assert_eq!(app.input_popup.as_ref().unwrap().buffer, "example.org/feed");
+    }
+
+    #[test]
+    fn opening_the_queue_opens_each_item_once() {
+        let mut shared = item("a", false);
+        shared.queued = true;
+        let mut groups = vec![
+            group(
+                "News",
+                vec![feed("World", vec![shared.clone(), item("b", false)])],
+            ),
+            group("Tech", vec![feed("Mirror", vec![shared.clone()])]),
+        ];
+        let mut queue = vec![shared];
+        let mut config = Config::default();
+        let links = queued_links(&groups, &queue, &config.opener);
+        let background = config.opener.background;
+        assert_eq!(links, [("https://example.com/a".to_string(), background)]);
+
+        config.opener.command = "true".into();
+        config.ui.queue_open_marks_read = true;
+        config.ui.queue_open_clears = true;
+        run_bulk(BulkAction::OpenQueue, &mut groups, &mut queue, &config);
+        assert!(queue.is_empty());
+        let shared: Vec<&Item> = groups
+            .iter()
+            .flat_map(|g| &g.feeds)
+            .flat_map(|f| &f.items)
+            .filter(|i| i.id == "a")
+            .collect();
+        assert!(shared.iter().all(|i| i.read && !i.queued));
+        assert_eq!((groups[0].unread_count, groups[1].unread_count), (1, 0));
+    }
+
+    #[test]
+    fn reversed_date_sort_keeps_undated_items_last() {
+        let undated = |id| Item {
+            timestamp: 0,
+            ..item(id, false)
+        };
+        let news = group(
+            "News",
+            vec![feed(
+                "World",
+                vec![
+                    undated("x"),
+                    item("b", false),
+                    undated("y"),
+                    item("a", false),
+                ],
+            )],
+        );
+        let mut app = app(vec![news.clone()]);
+        let titles = |app: &AppState| -> Vec<String> {
+            let feed = &news.feeds[0];
+            filtered_indices(app, ReadFilter::All, &news, feed)
+                .into_iter()
+                .map(|i| feed.items[i].title.clone())
+                .collect()
+        };
+        assert_eq!(titles(&app), ["Item a", "Item b", "Item x", "Item y"]);
+        app.config.ui.sort_reverse = true;
+        assert_eq!(titles(&app), ["Item b", "Item a", "Item x", "Item y"]);
+    }
 }
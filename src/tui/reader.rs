@@ -0,0 +1,128 @@
+//! Full-screen reader used when `opener.mode = "internal"`.
+
+use std::sync::{
+    OnceLock,
+    mpsc::{self, Receiver},
+};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+use regex::Regex;
+
+use super::html_to_text;
+use crate::net;
+
+/// An article fetched for reading inside the terminal.
+pub struct Reader {
+    pub title: String,
+    pub url: String,
+    pub text: String,
+    pub scroll: u16,
+    /// Pending fetch result; `None` once the article has arrived.
+    rx: Option<Receiver<Result<String, String>>>,
+}
+
+impl Reader {
+    /// Start fetching `url` in the background and show a placeholder until
+    /// it arrives.
+    pub fn load(title: &str, url: &str) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let target = url.to_string();
+        std::thread::spawn(move || {
+            let result = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| e.to_string())
+                .and_then(|rt| {
+                    rt.block_on(net::fetch_article(&target))
+                        .map_err(|e| e.to_string())
+                })
+                .map(|html| article_text(&html));
+            let _ = tx.send(result);
+        });
+        Self {
+            title: title.to_string(),
+            url: url.to_string(),
+            text: "Loading…".into(),
+            scroll: 0,
+            rx: Some(rx),
+        }
+    }
+
+    /// Pick up the fetched article if it has arrived.
+    pub fn poll(&mut self) {
+        let Some(rx) = &self.rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(Ok(text)) => self.text = text,
+            Ok(Err(e)) => self.text = format!("Failed to fetch {}: {}", self.url, e),
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.text = format!("Failed to fetch {}", self.url)
+            }
+        }
+        self.rx = None;
+    }
+
+    /// Scroll with the arrow, page and Home/End keys. Returns `false` when
+    /// the reader should close.
+    pub fn handle_key(&mut self, code: KeyCode, page: u16) -> bool {
+        let last = self.text.lines().count().saturating_sub(1) as u16;
+        match code {
+            KeyCode::Esc | KeyCode::Left => return false,
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => self.scroll = (self.scroll + 1).min(last),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(page),
+            KeyCode::PageDown | KeyCode::Char(' ') => {
+                self.scroll = self.scroll.saturating_add(page).min(last)
+            }
+            KeyCode::Home => self.scroll = 0,
+            KeyCode::End => self.scroll = last,
+            _ => {}
+        }
+        true
+    }
+
+    pub fn draw(&self, f: &mut Frame, area: Rect) {
+        let block = Block::default()
+            .title(format!("{} [Esc: close]", self.title))
+            .borders(Borders::ALL);
+        let paragraph = Paragraph::new(self.text.as_str())
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0));
+        f.render_widget(Clear, area);
+        f.render_widget(paragraph, area);
+    }
+}
+
+/// Reduce a web page to its readable text: scripts, styles and the head are
+/// dropped, and the `<article>` or `<body>` element is used when present.
+fn article_text(html: &str) -> String {
+    static NOISE: OnceLock<Regex> = OnceLock::new();
+    static ARTICLE: OnceLock<Regex> = OnceLock::new();
+    static BODY: OnceLock<Regex> = OnceLock::new();
+    static BLANKS: OnceLock<Regex> = OnceLock::new();
+    let noise = NOISE.get_or_init(|| {
+        Regex::new(r"(?is)<script\b.*?</script>|<style\b.*?</style>|<noscript\b.*?</noscript>|<head\b.*?</head>|<!--.*?-->")
+            .unwrap()
+    });
+    let article =
+        ARTICLE.get_or_init(|| Regex::new(r"(?is)<article\b[^>]*>(.*?)</article>").unwrap());
+    let body = BODY.get_or_init(|| Regex::new(r"(?is)<body\b[^>]*>(.*)</body>").unwrap());
+    let blanks = BLANKS.get_or_init(|| Regex::new(r"\n\s*\n(\s*\n)+").unwrap());
+    let cleaned = noise.replace_all(html, "");
+    let main = article
+        .captures(&cleaned)
+        .or_else(|| body.captures(&cleaned))
+        .and_then(|c| c.get(1))
+        .map_or(&cleaned[..], |m| m.as_str());
+    let text = html_to_text(main);
+    let text: Vec<&str> = text.lines().map(str::trim_end).collect();
+    blanks.replace_all(&text.join("\n"), "\n\n").into_owned()
+}